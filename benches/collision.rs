@@ -0,0 +1,36 @@
+use asteroids::bench::Scene;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Asteroids in each scene, with half as many bullets
+const SCENE_SIZES: [usize; 3] = [10, 50, 200];
+
+const SEED: u64 = 0;
+
+fn bounding_triangles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounding_triangles");
+    for asteroids in SCENE_SIZES {
+        let scene = Scene::new(asteroids, asteroids / 2, SEED);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(asteroids),
+            &scene,
+            |b, scene| b.iter(|| black_box(scene).bounding_triangles()),
+        );
+    }
+    group.finish();
+}
+
+fn collisions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collisions");
+    for asteroids in SCENE_SIZES {
+        let scene = Scene::new(asteroids, asteroids / 2, SEED);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(asteroids),
+            &scene,
+            |b, scene| b.iter(|| black_box(scene).collisions()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bounding_triangles, collisions);
+criterion_main!(benches);
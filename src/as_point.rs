@@ -1,17 +1,70 @@
 use sdl2::rect::Point;
 
 pub trait AsPoint {
+    /// Converts to a `Point`, truncating towards zero.
     fn as_point(&self) -> Point;
+
+    /// Converts to a `Point`, rounding to the nearest pixel.
+    fn to_point_rounded(&self) -> Point {
+        self.as_point()
+    }
 }
 
 impl AsPoint for glam::DVec2 {
     fn as_point(&self) -> Point {
         Point::from((self.x as i32, self.y as i32))
     }
+
+    fn to_point_rounded(&self) -> Point {
+        Point::from((self.x.round() as i32, self.y.round() as i32))
+    }
 }
 
 impl AsPoint for glam::Vec2 {
     fn as_point(&self) -> Point {
         Point::from((self.x as i32, self.y as i32))
     }
+
+    fn to_point_rounded(&self) -> Point {
+        Point::from((self.x.round() as i32, self.y.round() as i32))
+    }
+}
+
+impl AsPoint for glam::IVec2 {
+    fn as_point(&self) -> Point {
+        Point::from((self.x, self.y))
+    }
+}
+
+impl AsPoint for glam::UVec2 {
+    fn as_point(&self) -> Point {
+        Point::from((self.x as i32, self.y as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncating_and_rounding() {
+        let positive = glam::DVec2::new(2.7, 2.2);
+        assert_eq!(positive.as_point(), Point::new(2, 2));
+        assert_eq!(positive.to_point_rounded(), Point::new(3, 2));
+
+        // Truncating goes towards zero, which is up and left of negative points
+        let negative = glam::DVec2::new(-2.7, -2.2);
+        assert_eq!(negative.as_point(), Point::new(-2, -2));
+        assert_eq!(negative.to_point_rounded(), Point::new(-3, -2));
+
+        let single = glam::Vec2::new(-0.6, 0.6);
+        assert_eq!(single.as_point(), Point::new(0, 0));
+        assert_eq!(single.to_point_rounded(), Point::new(-1, 1));
+    }
+
+    #[test]
+    fn integer_vectors_are_exact() {
+        assert_eq!(glam::IVec2::new(-3, 4).as_point(), Point::new(-3, 4));
+        assert_eq!(glam::UVec2::new(3, 4).to_point_rounded(), Point::new(3, 4));
+    }
 }
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use crate::precision::{Float, Vector};
+use crate::MAX_ASTEROID_SIZE;
+
+/// How jagged asteroids of one size are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AsteroidShape {
+    /// At least 3
+    pub vert_count: usize,
+    /// Distance of the closest verts from the center, in pixels
+    pub min_distance: Float,
+    /// Distance of the furthest verts from the center, in pixels
+    pub max_distance: Float,
+}
+
+/// The shape of each asteroid size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsteroidShapeTable {
+    /// Indexed by size - 1
+    shapes: [AsteroidShape; MAX_ASTEROID_SIZE],
+    /// Hand-made verts to use instead of generating them, indexed by size - 1
+    templates: [Vec<Arc<[Vector]>>; MAX_ASTEROID_SIZE],
+}
+
+impl AsteroidShapeTable {
+    /// Sizes are numbered from 1.
+    pub fn shape(&self, size: usize) -> AsteroidShape {
+        assert!(
+            (1..=MAX_ASTEROID_SIZE).contains(&size),
+            "Invalid asteroid size"
+        );
+        self.shapes[size - 1]
+    }
+
+    /// The hand-made verts asteroids of `size` are picked from, if any.
+    pub fn templates(&self, size: usize) -> &[Arc<[Vector]>] {
+        &self.templates[size - 1]
+    }
+
+    /// Sets the hand-made verts of each size, indexed by size - 1, as loaded from a skin.
+    pub fn set_templates(&mut self, templates: [Vec<Arc<[Vector]>>; MAX_ASTEROID_SIZE]) {
+        self.templates = templates;
+    }
+
+    /// Sets the shape of one size from `<SIZE>:<VERTS>:<MIN>:<MAX>`, as given on the command line.
+    pub fn set_from_arg(&mut self, arg: &str) -> Result<(), String> {
+        let parts: Vec<&str> = arg.split(':').collect();
+        let &[size, vert_count, min_distance, max_distance] = &parts[..] else {
+            return Err(format!(
+                "Invalid asteroid shape `{arg}`, expected `<SIZE>:<VERTS>:<MIN>:<MAX>`"
+            ));
+        };
+        let size: usize = size
+            .parse()
+            .map_err(|e| format!("Invalid asteroid size `{size}`: {e}"))?;
+        if !(1..=MAX_ASTEROID_SIZE).contains(&size) {
+            return Err(format!(
+                "Asteroid size must be from 1 to {MAX_ASTEROID_SIZE}"
+            ));
+        }
+        let shape = AsteroidShape {
+            vert_count: vert_count
+                .parse()
+                .map_err(|e| format!("Invalid vert count `{vert_count}`: {e}"))?,
+            min_distance: min_distance
+                .parse()
+                .map_err(|e| format!("Invalid distance `{min_distance}`: {e}"))?,
+            max_distance: max_distance
+                .parse()
+                .map_err(|e| format!("Invalid distance `{max_distance}`: {e}"))?,
+        };
+        if shape.vert_count < 3 {
+            return Err("Asteroids need at least 3 verts".to_owned());
+        }
+        if !(0.0 < shape.min_distance && shape.min_distance <= shape.max_distance) {
+            return Err(
+                "Asteroid vert distances must be above 0, with the minimum first".to_owned(),
+            );
+        }
+        self.shapes[size - 1] = shape;
+        Ok(())
+    }
+}
+
+impl Default for AsteroidShapeTable {
+    fn default() -> Self {
+        AsteroidShapeTable {
+            shapes: [
+                AsteroidShape {
+                    vert_count: 6,
+                    min_distance: 20.0,
+                    max_distance: 28.0,
+                },
+                AsteroidShape {
+                    vert_count: 8,
+                    min_distance: 30.0,
+                    max_distance: 40.0,
+                },
+                AsteroidShape {
+                    vert_count: 14,
+                    min_distance: 39.0,
+                    max_distance: 50.0,
+                },
+            ],
+            templates: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_from_arg_rejects_invalid_shapes() {
+        let mut table = AsteroidShapeTable::default();
+        for arg in ["2:2:10:12", "0:6:10:12", "2:6:12:10", "2:6:0:10", "2:6:10"] {
+            assert!(table.set_from_arg(arg).is_err(), "{arg}");
+        }
+        assert_eq!(table, AsteroidShapeTable::default());
+    }
+}
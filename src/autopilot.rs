@@ -0,0 +1,280 @@
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+
+use crate::bounds::Bounds;
+use crate::precision::consts::{PI, TAU};
+use crate::precision::{consts, Float};
+use crate::settings::Settings;
+use crate::{
+    lead_target, nearest, rotation_towards, toroidal_delta, Entity, EntityKind, BULLET_TTL,
+};
+
+/// Asteroids closer than this ahead of the ship are avoided rather than aimed at, in pixels
+const DANGER_DISTANCE: Float = 150.0;
+
+/// Asteroids within this angle of the ship's heading count as ahead of it, in radians
+const DANGER_HALF_ANGLE: Float = consts::FRAC_PI_4;
+
+/// Fires once aimed within this angle of the target, in radians
+const AIM_TOLERANCE: Float = 0.1;
+
+/// Thrusts towards out-of-range targets until closing on them this fast, in pixels per frame
+const APPROACH_SPEED: Float = 1.5;
+
+/// Returns how far a ship at `rotation` must turn to reach `target`, in radians,
+/// positive if it should turn left.
+fn turn_towards(rotation: Float, target: Float) -> Float {
+    (target - rotation + PI).rem_euclid(TAU) - PI
+}
+
+/// What a computer-controlled ship wants to do this frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControlFlags {
+    pub accelerating: bool,
+    pub turning_left: bool,
+    pub turning_right: bool,
+    pub fire: bool,
+}
+
+fn key_event(down: bool, keycode: Keycode) -> Event {
+    if down {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    } else {
+        Event::KeyUp {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+}
+
+/// Decides how the player at `index` in `entities` should fly.
+///
+/// The ship turns away from asteroids close ahead of it, and otherwise turns to lead the nearest
+/// asteroid and shoots once aimed, if its bullets would reach it. Out of range, it thrusts
+/// towards the asteroid instead, up to a gentle approach speed.
+pub fn cpu_input(
+    entities: &[Entity],
+    index: usize,
+    bounds: Bounds,
+    settings: &Settings,
+) -> ControlFlags {
+    let body = &entities[index].body;
+    let asteroids = entities.iter().filter(|entity| {
+        matches!(
+            entity.kind,
+            EntityKind::Asteroid { .. } | EntityKind::Boss { .. }
+        )
+    });
+
+    let mut turn = 0.0;
+    let mut fire = false;
+    let mut accelerating = false;
+    if let Some(target) = nearest(body.position, asteroids, bounds, true) {
+        let delta = toroidal_delta(body.position, target.body.position, bounds);
+        let to_target = turn_towards(body.rotation, rotation_towards(delta));
+        if delta.length() < DANGER_DISTANCE && to_target.abs() < DANGER_HALF_ANGLE {
+            // Turn whichever way is quicker to put the asteroid behind the ship
+            turn = -to_target.signum();
+        } else {
+            let aim = lead_target(
+                body.position,
+                body.position + delta,
+                target.body.velocity - body.velocity,
+                settings.physics.muzzle_speed,
+            )
+            .unwrap_or(body.position + delta);
+            turn = turn_towards(body.rotation, rotation_towards(aim - body.position));
+            let aimed = turn.abs() < AIM_TOLERANCE;
+            let range = settings.physics.muzzle_speed * BULLET_TTL as Float;
+            if delta.length() <= range {
+                fire = aimed;
+            } else {
+                accelerating = aimed && body.velocity.dot(delta.normalize()) < APPROACH_SPEED;
+            }
+        }
+    }
+    let turning = turn.abs() >= AIM_TOLERANCE / 2.0;
+    ControlFlags {
+        accelerating,
+        turning_left: turning && turn > 0.0,
+        turning_right: turning && turn < 0.0,
+        fire,
+    }
+}
+
+/// Returns the key events that would make the player at `index` in `entities` fly as
+/// `cpu_input` decides, so it can be flown through the same event handling as human players.
+pub fn autopilot(
+    entities: &[Entity],
+    index: usize,
+    bounds: Bounds,
+    settings: &Settings,
+) -> Vec<Event> {
+    let player = &entities[index];
+    let EntityKind::Player {
+        accelerate,
+        turn_left,
+        turn_right,
+        fire,
+        ..
+    } = player.kind
+    else {
+        unreachable!()
+    };
+    let body = &player.body;
+    let controls = cpu_input(entities, index, bounds, settings);
+    // The turning keys are swapped while the screen is mirrored
+    let (turn_left, turn_right) = if settings.invert_y {
+        (turn_right, turn_left)
+    } else {
+        (turn_left, turn_right)
+    };
+
+    let mut events = vec![];
+    for (keycode, held, want) in [
+        (accelerate, body.accelerating, controls.accelerating),
+        (turn_left, body.turning_left, controls.turning_left),
+        (turn_right, body.turning_right, controls.turning_right),
+    ] {
+        if let Some(keycode) = keycode {
+            if held != want {
+                events.push(key_event(want, keycode));
+            }
+        }
+    }
+    if let (Some(fire), true) = (fire, controls.fire) {
+        events.push(key_event(true, fire));
+        events.push(key_event(false, fire));
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::asteroid_shape::AsteroidShapeTable;
+    use crate::bounds::WrapAxes;
+    use crate::precision::Vector;
+    use crate::{new_asteroid, new_player, Body, CONTROL_SCHEMES, SHIP_HULLS};
+
+    fn bounds() -> Bounds {
+        Bounds::new(UVec2::new(800, 600), WrapAxes::BOTH)
+    }
+
+    /// A player at the middle of the screen, facing north, and a size-2 asteroid at `position`.
+    fn player_and_asteroid(position: Vector) -> Vec<Entity> {
+        let player = new_player(
+            Vector::new(400.0, 300.0),
+            SHIP_HULLS[0].clone(),
+            3,
+            Some(&CONTROL_SCHEMES[0]),
+        );
+        let body = Body {
+            position,
+            prev_position: position,
+            ..Default::default()
+        };
+        let asteroid = new_asteroid(
+            2,
+            body,
+            &AsteroidShapeTable::default(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        vec![player, asteroid]
+    }
+
+    /// The keys pressed by `events`.
+    fn pressed(events: &[Event]) -> Vec<Keycode> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                Event::KeyDown { keycode, .. } => keycode,
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn turns_away_from_asteroid_close_ahead() {
+        // Slightly right of straight ahead, so turning left is the quicker way out
+        let entities = player_and_asteroid(Vector::new(410.0, 220.0));
+        let controls = cpu_input(&entities, 0, bounds(), &Settings::default());
+        assert!(controls.turning_left);
+        assert!(!controls.turning_right);
+        assert!(!controls.fire);
+    }
+
+    #[test]
+    fn autopilot_presses_and_releases_fire_when_aimed() {
+        let entities = player_and_asteroid(Vector::new(400.0, 100.0));
+        let EntityKind::Player { fire, .. } = entities[0].kind else {
+            unreachable!()
+        };
+        let events = autopilot(&entities, 0, bounds(), &Settings::default());
+        assert_eq!(pressed(&events), [fire.unwrap()]);
+        // Released straight away, so the next shot is a new press
+        assert!(matches!(
+            events.last(),
+            Some(Event::KeyUp { keycode, .. }) if *keycode == fire
+        ));
+    }
+
+    #[test]
+    fn autopilot_swaps_turning_keys_when_inverted() {
+        let entities = player_and_asteroid(Vector::new(410.0, 220.0));
+        let settings = Settings {
+            invert_y: true,
+            ..Settings::default()
+        };
+        let events = autopilot(&entities, 0, bounds(), &settings);
+        assert_eq!(pressed(&events), [CONTROL_SCHEMES[0].turn_right]);
+    }
+
+    #[test]
+    fn fires_on_asteroid_ahead_within_range() {
+        let entities = player_and_asteroid(Vector::new(400.0, 100.0));
+        let controls = cpu_input(&entities, 0, bounds(), &Settings::default());
+        assert_eq!(
+            controls,
+            ControlFlags {
+                fire: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn thrusts_towards_asteroid_out_of_range() {
+        let settings = Settings {
+            physics: crate::physics::Physics {
+                muzzle_speed: 1.0,
+                ..Default::default()
+            },
+            ..Settings::default()
+        };
+        let entities = player_and_asteroid(Vector::new(400.0, 100.0));
+        let controls = cpu_input(&entities, 0, bounds(), &settings);
+        assert_eq!(
+            controls,
+            ControlFlags {
+                accelerating: true,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Fixed scenes for the benchmarks in `benches/`, which can't reach the game's private items.
+
+use glam::UVec2;
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::asteroid_shape::AsteroidShapeTable;
+use crate::bounds::{Bounds, WrapAxes};
+use crate::precision::{consts, Vector};
+use crate::settings::Settings;
+use crate::{new_asteroid, new_bullet, Body, Entity};
+
+/// Randomly scattered asteroids and bullets on an 800x600 screen.
+///
+/// The same seed always gives the same scene, so results can be compared between runs.
+pub struct Scene {
+    entities: Vec<Entity>,
+    bounds: Bounds,
+}
+
+impl Scene {
+    pub fn new(asteroids: usize, bullets: usize, seed: u64) -> Scene {
+        let bounds = Bounds::new(UVec2::new(800, 600), WrapAxes::BOTH);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let random_body = |rng: &mut StdRng| Body {
+            position: bounds.size() * Vector::new(rng.gen(), rng.gen()),
+            velocity: Vector::new(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)),
+            rotation: rng.gen_range(0.0..consts::TAU),
+            ..Default::default()
+        };
+        let mut entities = vec![];
+        for _ in 0..asteroids {
+            let body = random_body(&mut rng);
+            let size = rng.gen_range(1..=3);
+            entities.push(new_asteroid(
+                size,
+                body,
+                &AsteroidShapeTable::default(),
+                &mut rng,
+            ));
+        }
+        for _ in 0..bullets {
+            entities.push(new_bullet(random_body(&mut rng), 0, None, 120));
+        }
+        Scene { entities, bounds }
+    }
+
+    /// Transforms every entity's bounding triangles into place, returning how many there are.
+    pub fn bounding_triangles(&self) -> usize {
+        self.entities
+            .iter()
+            .map(|entity| entity.bounding_triangles().count())
+            .sum()
+    }
+
+    /// Tests every pair of entities that can collide, returning how many pairs overlap.
+    pub fn collisions(&self) -> usize {
+        let settings = Settings::default();
+        self.entities
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| a.collides_with(b, &settings) && a.collision(b, self.bounds).is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_the_same_scene() {
+        let (a, b) = (Scene::new(50, 25, 1), Scene::new(50, 25, 1));
+        assert_eq!(a.entities.len(), 75);
+        assert_eq!(a.bounding_triangles(), b.bounding_triangles());
+        assert_eq!(a.collisions(), b.collisions());
+    }
+}
@@ -0,0 +1,316 @@
+use glam::UVec2;
+
+use crate::precision::{Float, Vector};
+
+/// Which edges of the play area wrap around to the opposite edge.
+///
+/// Wrapping entities bounce off edges that don't wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrapAxes {
+    /// The left and right edges wrap
+    pub x: bool,
+    /// The top and bottom edges wrap
+    pub y: bool,
+}
+
+impl WrapAxes {
+    pub const BOTH: WrapAxes = WrapAxes { x: true, y: true };
+
+    pub fn preset(name: &str) -> Option<WrapAxes> {
+        match name {
+            "both" => Some(WrapAxes::BOTH),
+            "horizontal" => Some(WrapAxes { x: true, y: false }),
+            "vertical" => Some(WrapAxes { x: false, y: true }),
+            // A walled arena
+            "none" => Some(WrapAxes { x: false, y: false }),
+            _ => None,
+        }
+    }
+}
+
+/// The size of the play area, and which of its edges wrap.
+/// Positions range from the origin to `size()`.
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    size: Vector,
+    wrap: WrapAxes,
+}
+
+impl Bounds {
+    pub fn new(size: UVec2, wrap: WrapAxes) -> Bounds {
+        Bounds {
+            size: Vector::new(size.x as Float, size.y as Float),
+            wrap,
+        }
+    }
+
+    pub fn size(self) -> Vector {
+        self.size
+    }
+
+    /// Returns true if positions wrap along `axis`, 0 for x or 1 for y.
+    pub fn wraps(self, axis: usize) -> bool {
+        match axis {
+            0 => self.wrap.x,
+            1 => self.wrap.y,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns true if the play area has no room along some axis, as when the window is minimized.
+    pub fn is_empty(self) -> bool {
+        self.size.x < 1.0 || self.size.y < 1.0
+    }
+
+    /// Wraps a position around the edges that wrap, into the play area along those axes.
+    ///
+    /// Axes with no room are left alone, since there is nowhere on them to wrap to.
+    pub fn wrap_position(self, mut position: Vector) -> Vector {
+        for axis in 0..2 {
+            if self.wraps(axis) && self.size[axis] > 0.0 {
+                position[axis] = position[axis].rem_euclid(self.size[axis]);
+            }
+        }
+        position
+    }
+
+    /// Returns true if the axis-aligned box from `min` to `max` is entirely within the play area.
+    pub fn contains_aabb(self, min: Vector, max: Vector) -> bool {
+        min.x >= 0.0 && min.y >= 0.0 && max.x <= self.size.x && max.y <= self.size.y
+    }
+
+    /// Returns where the line from the center to `position` crosses the edge of the area inset by
+    /// `margin`, or `position` itself if it is within that.
+    pub fn border_point(self, position: Vector, margin: Float) -> Vector {
+        let center = self.size / 2.0;
+        let half_size = (center - margin).max(Vector::ZERO);
+        let offset = position - center;
+        // How many times too far from the center the position is, on the further axis
+        let overshoot = (offset.abs() / half_size).max_element();
+        if overshoot > 1.0 {
+            center + offset / overshoot
+        } else {
+            position
+        }
+    }
+
+    /// Returns the ends of grid lines across the play area, about `spacing` apart.
+    ///
+    /// Along wrapping axes the spacing is stretched a little so a whole number of cells fits,
+    /// so the grid carries on seamlessly across the wrapped edges.
+    pub fn grid_lines(self, spacing: Float) -> Vec<(Vector, Vector)> {
+        let mut lines = vec![];
+        for axis in 0..2 {
+            let length = self.size[axis];
+            let cells = (length / spacing).round().max(1.0);
+            let step = if self.wraps(axis) {
+                length / cells
+            } else {
+                spacing
+            };
+            let mut offset = step;
+            while offset < length {
+                let mut start = Vector::ZERO;
+                start[axis] = offset;
+                let mut end = self.size;
+                end[axis] = offset;
+                lines.push((start, end));
+                offset += step;
+            }
+        }
+        lines
+    }
+
+    /// Returns the positions at which something within `reach` of `position` must be drawn for
+    /// it to wrap around the edges, including `position` itself.
+    pub fn wrapped_copies(self, position: Vector, reach: Float) -> impl Iterator<Item = Vector> {
+        self.wrap_offsets(position - reach, position + reach)
+            .map(move |offset| position + offset)
+    }
+
+    /// Returns the offsets at which something spanning the axis-aligned box from `min` to `max`
+    /// must be drawn for it to wrap around the edges, including the zero offset.
+    ///
+    /// The box may be any size, even larger than the play area.
+    pub fn wrap_offsets(self, min: Vector, max: Vector) -> impl Iterator<Item = Vector> {
+        // Every whole number of screens `k` for which the box, moved by `k` screens,
+        // overlaps the play area
+        let steps = |axis: usize| {
+            if !self.wraps(axis) {
+                return 0..=0;
+            }
+            let size = self.size[axis];
+            let first = (-max[axis] / size).floor() as i32 + 1;
+            let last = (1.0 - min[axis] / size).ceil() as i32 - 1;
+            // Always include the zero offset, even for a box entirely off-screen
+            first.min(0)..=last.max(0)
+        };
+        let dxs = steps(0);
+        let dys = steps(1);
+        let size = self.size;
+        dys.flat_map(move |dy| {
+            dxs.clone().map(move |dx| {
+                size * Vector {
+                    x: dx as Float,
+                    y: dy as Float,
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds() -> Bounds {
+        Bounds::new(UVec2::new(800, 600), WrapAxes::BOTH)
+    }
+
+    #[test]
+    fn wrap_position_negative() {
+        let wrapped = bounds().wrap_position(Vector::new(-10.0, -650.0));
+        assert_eq!(wrapped, Vector::new(790.0, 550.0));
+    }
+
+    #[test]
+    fn wrap_position_over_bounds() {
+        let wrapped = bounds().wrap_position(Vector::new(810.0, 1800.0));
+        assert_eq!(wrapped, Vector::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn wrap_position_inside_is_unchanged() {
+        let position = Vector::new(0.0, 599.5);
+        assert_eq!(bounds().wrap_position(position), position);
+    }
+
+    #[test]
+    fn box_over_a_corner_is_drawn_at_four_offsets() {
+        let offsets = bounds()
+            .wrap_offsets(Vector::new(790.0, -5.0), Vector::new(810.0, 5.0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            offsets,
+            [
+                Vector::new(-800.0, 0.0),
+                Vector::new(0.0, 0.0),
+                Vector::new(-800.0, 600.0),
+                Vector::new(0.0, 600.0),
+            ]
+        );
+    }
+
+    /// Returns true if the box from `min` to `max` covers any of the play area.
+    fn overlaps(bounds: Bounds, min: Vector, max: Vector) -> bool {
+        let size = bounds.size();
+        min.x < size.x && min.y < size.y && max.x > 0.0 && max.y > 0.0
+    }
+
+    #[test]
+    fn sprite_larger_than_bounds_is_drawn_everywhere_it_shows() {
+        let bounds = bounds();
+        let (min, max) = (Vector::new(-900.0, -700.0), Vector::new(900.0, 700.0));
+        let offsets = bounds.wrap_offsets(min, max).collect::<Vec<_>>();
+
+        let mut expected = vec![];
+        for dy in -5..=5 {
+            for dx in -5..=5 {
+                let offset = bounds.size() * Vector::new(dx as Float, dy as Float);
+                if overlaps(bounds, min + offset, max + offset) {
+                    expected.push(offset);
+                }
+            }
+        }
+        // Four copies along each axis
+        assert_eq!(expected.len(), 16);
+        assert_eq!(offsets, expected);
+    }
+
+    #[test]
+    fn wrapped_copies_near_corner() {
+        let copies = bounds()
+            .wrapped_copies(Vector::new(795.0, 3.0), 10.0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            copies,
+            [
+                Vector::new(-5.0, 3.0),
+                Vector::new(795.0, 3.0),
+                Vector::new(-5.0, 603.0),
+                Vector::new(795.0, 603.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrapped_copies_away_from_edges() {
+        let position = Vector::new(400.0, 300.0);
+        let copies = bounds().wrapped_copies(position, 10.0).collect::<Vec<_>>();
+        assert_eq!(copies, [position]);
+    }
+
+    #[test]
+    fn vertical_edges_only_wrap_vertically() {
+        let bounds = Bounds::new(UVec2::new(800, 600), WrapAxes { x: false, y: true });
+        assert_eq!(
+            bounds.wrap_position(Vector::new(-10.0, -10.0)),
+            Vector::new(-10.0, 590.0)
+        );
+        let offsets = bounds
+            .wrap_offsets(Vector::new(-5.0, -5.0), Vector::new(5.0, 5.0))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [Vector::new(0.0, 0.0), Vector::new(0.0, 600.0)]);
+    }
+
+    #[test]
+    fn border_point_clamps_to_the_nearer_edge() {
+        let bounds = bounds();
+        // Straight out from the center, and off towards a corner
+        assert_eq!(
+            bounds.border_point(Vector::new(1200.0, 300.0), 10.0),
+            Vector::new(790.0, 300.0)
+        );
+        assert_eq!(
+            bounds.border_point(Vector::new(400.0, -300.0), 10.0),
+            Vector::new(400.0, 10.0)
+        );
+        assert_eq!(
+            bounds.border_point(Vector::new(-400.0, 750.0), 0.0),
+            Vector::new(0.0, 525.0)
+        );
+    }
+
+    #[test]
+    fn border_point_leaves_inside_positions_alone() {
+        let position = Vector::new(100.0, 550.0);
+        assert_eq!(bounds().border_point(position, 10.0), position);
+    }
+
+    #[test]
+    fn grid_lines_fit_whole_cells_along_wrapping_axes() {
+        let offsets = |bounds: Bounds, axis: usize| {
+            let lines = bounds.grid_lines(110.0);
+            lines
+                .iter()
+                .filter(|(start, end)| start[axis] == end[axis])
+                .map(|(start, _)| start[axis])
+                .collect::<Vec<_>>()
+        };
+        // 800 across fits about 7 cells, and 600 down about 5
+        let xs = offsets(bounds(), 0);
+        assert_eq!(xs.len(), 6);
+        for (i, x) in xs.iter().enumerate() {
+            assert!((x - (i + 1) as Float * 800.0 / 7.0).abs() < 1e-3, "{xs:?}");
+        }
+        assert_eq!(offsets(bounds(), 1), [120.0, 240.0, 360.0, 480.0]);
+
+        // Exactly the spacing apart when the edges don't wrap
+        let walled = Bounds::new(UVec2::new(800, 600), WrapAxes::preset("none").unwrap());
+        assert_eq!(
+            offsets(walled, 0),
+            [110.0, 220.0, 330.0, 440.0, 550.0, 660.0, 770.0]
+        );
+        assert_eq!(offsets(walled, 1), [110.0, 220.0, 330.0, 440.0, 550.0]);
+    }
+}
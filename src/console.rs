@@ -0,0 +1,147 @@
+use sdl2::render::WindowCanvas;
+
+use crate::bounds::Bounds;
+use crate::lines::LineRenderer;
+use crate::physics::Physics;
+use crate::precision::{Float, Vector};
+use crate::shade;
+use crate::text::{draw_text, GLYPH_HEIGHT};
+use crate::theme::Theme;
+
+/// Pixels per grid unit of the console text
+const TEXT_SCALE: Float = 2.0;
+
+/// The key that opens and closes the console, which is never typed into it
+pub const TOGGLE_CHAR: char = '`';
+
+/// Runs a console command, returning what to echo back.
+///
+/// `<name> <value>` sets a physics value and `<name>` shows it. Names are `thrust`, `drag`,
+/// `turn`, `muzzle` and `bullet`. Values are checked the way command line flags are, so NaN and
+/// out of range values are rejected rather than breaking the game.
+pub fn run_command(command: &str, physics: &mut Physics) -> Result<String, String> {
+    let mut words = command.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("Expected a name".to_owned());
+    };
+    let field = match name {
+        "thrust" => &mut physics.thrust,
+        "drag" => &mut physics.drag,
+        "turn" => &mut physics.turn_speed,
+        "muzzle" => &mut physics.muzzle_speed,
+        "bullet" => &mut physics.bullet_scale,
+        _ => return Err(format!("Unknown name {name}")),
+    };
+    match (words.next(), words.next()) {
+        (None, _) => {}
+        (Some(value), None) => {
+            let parsed: Float = value
+                .parse()
+                .map_err(|e| format!("Invalid value {value}: {e}"))?;
+            let limits = match name {
+                "drag" if !(0.0..1.0).contains(&parsed) => Some("at least 0 and below 1"),
+                "bullet" if !(parsed.is_finite() && parsed > 0.0) => Some("above 0"),
+                "thrust" | "turn" | "muzzle" if !(0.0..Float::INFINITY).contains(&parsed) => {
+                    Some("at least 0")
+                }
+                _ => None,
+            };
+            if let Some(limits) = limits {
+                return Err(format!("{name} must be {limits}"));
+            }
+            *field = parsed;
+        }
+        (Some(_), Some(_)) => return Err("Expected one value".to_owned()),
+    }
+    Ok(format!("{name}: {field}"))
+}
+
+/// A line of text input for tuning physics while playing, toggled with `TOGGLE_CHAR`.
+#[derive(Debug, Default)]
+pub struct Console {
+    input: String,
+    /// What the last command echoed
+    output: Option<String>,
+}
+
+impl Console {
+    pub fn type_text(&mut self, text: &str) {
+        self.input
+            .extend(text.chars().filter(|&c| c != TOGGLE_CHAR));
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Runs and clears the typed command.
+    pub fn submit(&mut self, physics: &mut Physics) {
+        let command = std::mem::take(&mut self.input);
+        self.output = Some(match run_command(&command, physics) {
+            Ok(output) => output,
+            Err(e) => format!("Error: {e}"),
+        });
+    }
+
+    /// Draws the input and the last output in the bottom left corner.
+    pub fn draw(
+        &self,
+        lines: &mut LineRenderer,
+        canvas: &mut WindowCanvas,
+        bounds: Bounds,
+        theme: &Theme,
+    ) {
+        let line_height = (GLYPH_HEIGHT + 3.0) * TEXT_SCALE;
+        let input_position = Vector::new(10.0, bounds.size().y - 10.0 - line_height);
+        let input = format!("> {}", self.input);
+        draw_text(lines, canvas, &input, input_position, TEXT_SCALE, theme.hud);
+        if let Some(output) = &self.output {
+            let position = input_position - Vector::new(0.0, line_height);
+            let color = shade(theme.hud, 0.7);
+            draw_text(lines, canvas, output, position, TEXT_SCALE, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_set_and_show_values() {
+        let mut physics = Physics::default();
+        assert_eq!(
+            run_command("thrust 0.2", &mut physics),
+            Ok("thrust: 0.2".to_owned())
+        );
+        assert_eq!(physics.thrust, 0.2);
+        assert_eq!(
+            run_command("  turn   0.5 ", &mut physics),
+            Ok("turn: 0.5".to_owned())
+        );
+        assert_eq!(physics.turn_speed, 0.5);
+        assert_eq!(
+            run_command("drag", &mut physics),
+            Ok("drag: 0.01".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_commands_change_nothing() {
+        let mut physics = Physics::default();
+        for command in [
+            "",
+            "gravity 9.8",
+            "thrust fast",
+            "thrust 0.2 0.3",
+            "thrust -0.1",
+            "turn NaN",
+            "muzzle inf",
+            "drag 1",
+            "bullet 0",
+        ] {
+            assert!(run_command(command, &mut physics).is_err(), "{command:?}");
+        }
+        assert_eq!(format!("{physics:?}"), format!("{:?}", Physics::default()));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::precision::Float;
+use crate::MAX_ASTEROID_SIZE;
+
+/// Controls how hard each wave is, and how quickly that ramps up.
+#[derive(Debug, Clone, Copy)]
+pub struct Difficulty {
+    /// The name of the preset this difficulty came from
+    pub name: &'static str,
+    /// Multiplier on asteroid speed in the first wave
+    pub base_speed: Float,
+    /// Added to the asteroid speed multiplier each wave
+    pub speed_ramp: Float,
+    /// Number of asteroids in the first wave
+    pub base_asteroid_count: usize,
+    /// Extra asteroids added each wave
+    pub asteroid_count_ramp: usize,
+    /// Size of the largest asteroids in the first wave
+    pub base_max_size: usize,
+    /// The largest asteroid size grows by one every this many waves
+    pub waves_per_size: usize,
+    /// Hits it takes to split an asteroid of the largest size. Smaller ones always take one.
+    pub large_asteroid_hits: u32,
+}
+
+impl Difficulty {
+    pub const EASY: Difficulty = Difficulty {
+        name: "easy",
+        base_speed: 0.6,
+        speed_ramp: 0.05,
+        base_asteroid_count: 2,
+        asteroid_count_ramp: 1,
+        base_max_size: 1,
+        waves_per_size: 3,
+        large_asteroid_hits: 1,
+    };
+    pub const NORMAL: Difficulty = Difficulty {
+        name: "normal",
+        base_speed: 1.0,
+        speed_ramp: 0.1,
+        base_asteroid_count: 3,
+        asteroid_count_ramp: 1,
+        base_max_size: 2,
+        waves_per_size: 2,
+        large_asteroid_hits: 1,
+    };
+    pub const HARD: Difficulty = Difficulty {
+        name: "hard",
+        base_speed: 1.5,
+        speed_ramp: 0.15,
+        base_asteroid_count: 4,
+        asteroid_count_ramp: 2,
+        base_max_size: 3,
+        waves_per_size: 1,
+        large_asteroid_hits: 3,
+    };
+
+    /// In the order `next` cycles through them.
+    pub const PRESETS: [Difficulty; 3] = [Difficulty::EASY, Difficulty::NORMAL, Difficulty::HARD];
+
+    pub fn preset(name: &str) -> Option<Difficulty> {
+        Difficulty::PRESETS
+            .into_iter()
+            .find(|difficulty| difficulty.name == name)
+    }
+
+    /// The preset after this one, wrapping around from the hardest to the easiest.
+    pub fn next(&self) -> Difficulty {
+        let index = Difficulty::PRESETS
+            .iter()
+            .position(|difficulty| difficulty.name == self.name)
+            .map_or(0, |index| (index + 1) % Difficulty::PRESETS.len());
+        Difficulty::PRESETS[index]
+    }
+
+    /// Waves are numbered from 1.
+    pub fn asteroid_speed_multiplier(&self, wave: usize) -> Float {
+        self.base_speed + self.speed_ramp * wave.saturating_sub(1) as Float
+    }
+
+    pub fn asteroid_count(&self, wave: usize) -> usize {
+        self.base_asteroid_count + self.asteroid_count_ramp * wave.saturating_sub(1)
+    }
+
+    pub fn max_asteroid_size(&self, wave: usize) -> usize {
+        let size = self.base_max_size + wave.saturating_sub(1) / self.waves_per_size.max(1);
+        size.clamp(1, MAX_ASTEROID_SIZE)
+    }
+
+    /// How many hits an asteroid of `size` takes to split, at least 1.
+    pub fn asteroid_hits(&self, size: usize) -> u32 {
+        if size >= MAX_ASTEROID_SIZE {
+            self.large_asteroid_hits.max(1)
+        } else {
+            1
+        }
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::NORMAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_found_by_name() {
+        assert_eq!(
+            Difficulty::preset("hard").unwrap().base_speed,
+            Difficulty::HARD.base_speed
+        );
+        assert!(Difficulty::preset("impossible").is_none());
+    }
+
+    #[test]
+    fn next_cycles_through_presets() {
+        let names: Vec<&str> =
+            std::iter::successors(Some(Difficulty::EASY), |difficulty| Some(difficulty.next()))
+                .take(4)
+                .map(|difficulty| difficulty.name)
+                .collect();
+        assert_eq!(names, ["easy", "normal", "hard", "easy"]);
+    }
+
+    #[test]
+    fn hard_ramps_speed_faster_than_easy() {
+        for wave in 1..=20 {
+            assert!(
+                Difficulty::HARD.asteroid_speed_multiplier(wave)
+                    > Difficulty::EASY.asteroid_speed_multiplier(wave)
+            );
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::{Entity, EntityKind, RemoveReason};
+
+/// An opt-in log of what happens to entities each frame, for diagnosing odd behavior.
+///
+/// Each line is one record of space-separated `key=value` pairs, e.g.
+/// `frame=12 event=split id=40 size=3 x=401.2 y=87.0`.
+pub struct EventLog {
+    /// `None` if logging is disabled
+    out: Option<Box<dyn Write>>,
+    frame: u64,
+}
+
+impl EventLog {
+    pub fn disabled() -> EventLog {
+        EventLog {
+            out: None,
+            frame: 0,
+        }
+    }
+
+    /// Opens a log writing to the given file, or to stderr if `path` is `-`.
+    pub fn open(path: &str) -> std::io::Result<EventLog> {
+        let out: Box<dyn Write> = if path == "-" {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+        Ok(EventLog::new(out))
+    }
+
+    /// A log writing to `out`.
+    pub fn new(out: Box<dyn Write>) -> EventLog {
+        EventLog {
+            out: Some(out),
+            frame: 0,
+        }
+    }
+
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    pub fn spawn(&mut self, entity: &Entity) {
+        self.entity_record("spawn", entity);
+        let Some(out) = &mut self.out else { return };
+        writeln!(out).ok();
+    }
+
+    pub fn remove(&mut self, entity: &Entity, reason: RemoveReason) {
+        self.entity_record("remove", entity);
+        let Some(out) = &mut self.out else { return };
+        let reason = match reason {
+            RemoveReason::Expired => "expired",
+            RemoveReason::Destroyed => "destroyed",
+            RemoveReason::Collected => "collected",
+        };
+        writeln!(out, " reason={reason}").ok();
+    }
+
+    pub fn collision(&mut self, a: &Entity, b: &Entity) {
+        let Some(out) = &mut self.out else { return };
+        writeln!(
+            out,
+            "frame={} event=collision id={} kind={} x={:.1} y={:.1} other_id={} other_kind={} other_x={:.1} other_y={:.1}",
+            self.frame,
+            a.id,
+            kind_name(&a.kind),
+            a.body.position.x,
+            a.body.position.y,
+            b.id,
+            kind_name(&b.kind),
+            b.body.position.x,
+            b.body.position.y,
+        )
+        .ok();
+    }
+
+    pub fn split(&mut self, asteroid: &Entity) {
+        let Some(out) = &mut self.out else { return };
+        let EntityKind::Asteroid { size, .. } = asteroid.kind else {
+            return;
+        };
+        writeln!(
+            out,
+            "frame={} event=split id={} size={} x={:.1} y={:.1}",
+            self.frame, asteroid.id, size, asteroid.body.position.x, asteroid.body.position.y,
+        )
+        .ok();
+    }
+
+    /// Records the score a game ended with, when the window is closed.
+    pub fn game_over(&mut self, score: u64) {
+        let Some(out) = &mut self.out else { return };
+        writeln!(out, "frame={} event=game_over score={score}", self.frame).ok();
+    }
+
+    /// Writes the fields every entity record has, leaving the line open for more.
+    fn entity_record(&mut self, event: &str, entity: &Entity) {
+        let Some(out) = &mut self.out else { return };
+        write!(
+            out,
+            "frame={} event={} id={} kind={} x={:.1} y={:.1}",
+            self.frame,
+            event,
+            entity.id,
+            kind_name(&entity.kind),
+            entity.body.position.x,
+            entity.body.position.y,
+        )
+        .ok();
+        if let EntityKind::Bullet { owner, .. } = entity.kind {
+            write!(out, " owner={owner}").ok();
+        }
+    }
+}
+
+fn kind_name(kind: &EntityKind) -> &'static str {
+    match kind {
+        EntityKind::Asteroid { .. } => "asteroid",
+        EntityKind::Boss { .. } => "boss",
+        EntityKind::Bullet { .. } => "bullet",
+        EntityKind::Debris { .. } => "debris",
+        EntityKind::Player { .. } => "player",
+        EntityKind::Powerup { .. } => "powerup",
+        EntityKind::Shockwave { .. } => "shockwave",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::asteroid_shape::AsteroidShapeTable;
+    use crate::precision::Vector;
+    use crate::{new_asteroid, Body};
+
+    /// A writer whose output can still be read after it's boxed into a log.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.borrow().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_owned)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn records_carry_their_frame_and_details() {
+        let buffer = SharedBuffer::default();
+        let mut log = EventLog::new(Box::new(buffer.clone()));
+        let body = Body {
+            position: Vector::new(401.25, 87.0),
+            ..Default::default()
+        };
+        let asteroid = new_asteroid(
+            3,
+            body,
+            &AsteroidShapeTable::default(),
+            &mut StdRng::seed_from_u64(0),
+        );
+
+        log.next_frame();
+        log.spawn(&asteroid);
+        log.next_frame();
+        log.split(&asteroid);
+        log.game_over(1250);
+
+        let id = asteroid.id;
+        assert_eq!(
+            buffer.lines(),
+            [
+                format!("frame=1 event=spawn id={id} kind=asteroid x=401.2 y=87.0"),
+                format!("frame=2 event=split id={id} size=3 x=401.2 y=87.0"),
+                "frame=2 event=game_over score=1250".to_owned(),
+            ]
+        );
+    }
+}
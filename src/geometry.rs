@@ -0,0 +1,176 @@
+use crate::precision::{Float, Vector};
+
+/// Returns true if `point` is inside or on the edge of `triangle`, in either winding order.
+pub fn point_in_triangle(point: Vector, [a, b, c]: [Vector; 3]) -> bool {
+    let d1 = (b - a).perp_dot(point - a);
+    let d2 = (c - b).perp_dot(point - b);
+    let d3 = (a - c).perp_dot(point - c);
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+/// Returns the area of `triangle`, which is zero if its corners are collinear.
+pub fn triangle_area([a, b, c]: [Vector; 3]) -> Float {
+    (b - a).perp_dot(c - a).abs() / 2.0
+}
+
+/// Returns the area enclosed by the cyclic polygon `verts`, in either winding order.
+pub fn polygon_area(verts: &[Vector]) -> Float {
+    // Shoelace formula
+    let twice_signed_area: Float = verts
+        .iter()
+        .zip(verts.iter().cycle().skip(1))
+        .map(|(&a, &b)| a.perp_dot(b))
+        .sum();
+    twice_signed_area.abs() / 2.0
+}
+
+/// Returns how far along the segment from `p1` to `p2` it intersects the segment from `q1` to `q2`,
+/// from 0 at `p1` to 1 at `p2`, or `None` if they don't intersect or are parallel.
+pub fn segment_intersection(p1: Vector, p2: Vector, q1: Vector, q2: Vector) -> Option<Float> {
+    let r = p2 - p1;
+    let s = q2 - q1;
+    let denominator = r.perp_dot(s);
+    if denominator == 0.0 {
+        return None;
+    }
+    let t = (q1 - p1).perp_dot(s) / denominator;
+    let u = (q1 - p1).perp_dot(r) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+/// Returns how far along the segment from `p1` to `p2` it first touches `triangle`,
+/// from 0 at `p1` to 1 at `p2`, or `None` if it misses.
+pub fn segment_triangle_intersection(
+    p1: Vector,
+    p2: Vector,
+    triangle: [Vector; 3],
+) -> Option<Float> {
+    if point_in_triangle(p1, triangle) {
+        return Some(0.0);
+    }
+    let [a, b, c] = triangle;
+    [(a, b), (b, c), (c, a)]
+        .into_iter()
+        .filter_map(|(q1, q2)| segment_intersection(p1, p2, q1, q2))
+        .min_by(Float::total_cmp)
+}
+
+/// Returns the part of the convex polygon `subject` inside `triangle`, as a convex polygon, which
+/// has no verts if they don't overlap.
+pub fn clip_to_triangle(subject: &[Vector], [a, b, c]: [Vector; 3]) -> Vec<Vector> {
+    // Which side of each edge is inside depends on the winding order
+    let winding = (b - a).perp_dot(c - a).signum();
+    let mut polygon = subject.to_vec();
+    for (start, end) in [(a, b), (b, c), (c, a)] {
+        let inside = |point: Vector| winding * (end - start).perp_dot(point - start) >= 0.0;
+        let input = std::mem::take(&mut polygon);
+        // Sutherland-Hodgman: keeps the verts inside the edge, and where the polygon crosses it
+        for (&p1, &p2) in input.iter().zip(input.iter().cycle().skip(1)) {
+            if inside(p1) {
+                polygon.push(p1);
+            }
+            if inside(p1) != inside(p2) {
+                let side1 = (end - start).perp_dot(p1 - start);
+                let side2 = (end - start).perp_dot(p2 - start);
+                polygon.push(p1 + (p2 - p1) * (side1 / (side1 - side2)));
+            }
+        }
+    }
+    polygon
+}
+
+/// Returns the unit vector from `a` towards `b` along which they overlap least, and how far `b`
+/// would have to move along it to stop overlapping, or `None` if they don't overlap.
+///
+/// Triangles are convex, so by the separating axis theorem they overlap unless the normal of an
+/// edge of one of them separates them.
+pub fn triangle_penetration(a: [Vector; 3], b: [Vector; 3]) -> Option<(Vector, Float)> {
+    let edge_normals =
+        |[p, q, r]: [Vector; 3]| [q - p, r - q, p - r].map(|edge| edge.perp().normalize_or_zero());
+    let project = |triangle: [Vector; 3], axis: Vector| {
+        triangle.iter().map(|point| point.dot(axis)).fold(
+            (Float::INFINITY, Float::NEG_INFINITY),
+            |(min, max), along| (min.min(along), max.max(along)),
+        )
+    };
+    let mut least: Option<(Vector, Float)> = None;
+    for axis in edge_normals(a).into_iter().chain(edge_normals(b)) {
+        if axis == Vector::ZERO {
+            continue;
+        }
+        let (min_a, max_a) = project(a, axis);
+        let (min_b, max_b) = project(b, axis);
+        // How far `b` would have to move along `axis` to clear `a`, forwards and backwards
+        let (forwards, backwards) = (max_a - min_b, max_b - min_a);
+        if forwards < 0.0 || backwards < 0.0 {
+            return None;
+        }
+        let (normal, depth) = if forwards <= backwards {
+            (axis, forwards)
+        } else {
+            (-axis, backwards)
+        };
+        if least.is_none_or(|(_, least_depth)| depth < least_depth) {
+            least = Some((normal, depth));
+        }
+    }
+    least
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: [Vector; 3] = [
+        Vector::new(0.0, 0.0),
+        Vector::new(10.0, 0.0),
+        Vector::new(0.0, 10.0),
+    ];
+
+    #[test]
+    fn segment_enters_triangle_at_its_nearest_edge() {
+        let t = segment_triangle_intersection(
+            Vector::new(-10.0, 2.0),
+            Vector::new(10.0, 2.0),
+            TRIANGLE,
+        );
+        assert_eq!(t, Some(0.5));
+        // Starting inside
+        let t =
+            segment_triangle_intersection(Vector::new(2.0, 2.0), Vector::new(20.0, 2.0), TRIANGLE);
+        assert_eq!(t, Some(0.0));
+    }
+
+    #[test]
+    fn segment_past_triangle_misses() {
+        let t = segment_triangle_intersection(
+            Vector::new(-10.0, 12.0),
+            Vector::new(20.0, 12.0),
+            TRIANGLE,
+        );
+        assert_eq!(t, None);
+        // Pointing at it, but stopping short
+        let t = segment_triangle_intersection(
+            Vector::new(-10.0, 2.0),
+            Vector::new(-1.0, 2.0),
+            TRIANGLE,
+        );
+        assert_eq!(t, None);
+    }
+
+    #[test]
+    fn polygon_area_ignores_winding_order() {
+        let mut square = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(2.0, 0.0),
+            Vector::new(2.0, 2.0),
+            Vector::new(0.0, 2.0),
+        ];
+        assert_eq!(polygon_area(&square), 4.0);
+        square.reverse();
+        assert_eq!(polygon_area(&square), 4.0);
+        assert_eq!(polygon_area(&TRIANGLE), 50.0);
+    }
+}
@@ -0,0 +1,6614 @@
+use asteroid_shape::AsteroidShapeTable;
+use bounds::{Bounds, WrapAxes};
+use console::Console;
+use difficulty::Difficulty;
+use either::Either;
+use event_log::EventLog;
+use glam::UVec2;
+use itertools::Itertools;
+use lines::LineRenderer;
+use low_res::LowRes;
+use menu::{PauseMenu, PauseOption};
+use pacing::{Pacer, MAX_CATCH_UP_TICKS};
+use precision::{consts, Float, Matrix, Vector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget, WindowCanvas};
+use sdl2::surface::Surface;
+use settings::{PresentMode, Settings, MAX_PLAYERS};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use theme::Theme;
+use trails::Trails;
+
+mod as_point;
+mod asteroid_shape;
+mod autopilot;
+#[doc(hidden)]
+pub mod bench;
+mod bounds;
+mod console;
+mod difficulty;
+mod event_log;
+mod geometry;
+mod lines;
+mod low_res;
+mod menu;
+mod pacing;
+mod physics;
+mod precision;
+mod settings;
+mod skin;
+mod text;
+mod theme;
+mod trails;
+
+/// How long the charge key must be held for a charged shot, in seconds
+const CHARGE_SECONDS: Float = 1.0;
+
+/// Screen-clearing bombs each player starts with
+const STARTING_BOMBS: u32 = 2;
+
+/// How long a player is invulnerable after respawning, in seconds
+const RESPAWN_INVULNERABLE_SECONDS: Float = 3.0;
+
+/// Speed added to a player bumping into an asteroid in bumper mode, in pixels per frame
+const BUMPER_KNOCKBACK: Float = 3.0;
+
+/// How long a player bumping into an asteroid can't thrust, in seconds
+const BUMPER_STUN_SECONDS: Float = 0.5;
+
+/// How many times a bullet can ricochet off the screen edges before it expires
+const RICOCHET_BOUNCES: u32 = 3;
+
+/// Points for destroying an asteroid of the given size; smaller asteroids are worth more
+fn asteroid_points(size: usize) -> u64 {
+    match size {
+        1 => 100,
+        2 => 50,
+        _ => 20,
+    }
+}
+
+/// Chance that destroying a smallest asteroid leaves a powerup behind
+const POWERUP_DROP_CHANCE: f64 = 0.1;
+
+/// How long a powerup lasts before disappearing, in seconds
+const POWERUP_SECONDS: Float = 10.0;
+
+/// How far powerups rock either way, in radians
+const POWERUP_WOBBLE: Float = 0.3;
+
+/// How large a bomb's shockwave grows before disappearing, in pixels
+const SHOCKWAVE_MAX_RADIUS: Float = 300.0;
+
+/// How quickly a bomb's shockwave grows, in pixels per frame
+const SHOCKWAVE_GROWTH: Float = 6.0;
+
+/// How many sides the circle a shockwave is drawn as has
+const SHOCKWAVE_SEGMENTS: usize = 32;
+
+/// How far away a player's magnet reaches, in pixels
+const MAGNET_RADIUS: Float = 250.0;
+
+/// A player's magnet reaches this far either side of the ship's heading, in radians
+const MAGNET_HALF_ANGLE: Float = consts::FRAC_PI_6;
+
+/// How strongly a magnet pulls a powerup, in pixels per frame per frame
+const MAGNET_STRENGTH: Float = 0.08;
+
+/// Asteroids of this size split into asteroids of size one less, down to size 1
+const MAX_ASTEROID_SIZE: usize = 3;
+
+/// How long a fired bullet lasts, in frames
+const BULLET_TTL: u64 = 120;
+
+/// Time between spread shots, in seconds
+const SPREAD_COOLDOWN_SECONDS: Float = 0.5;
+
+/// Time between other shots while the fire key is held with `--auto-fire`, in seconds
+const AUTO_FIRE_COOLDOWN_SECONDS: Float = 0.2;
+
+/// Every this many waves, a boss joins the wave
+const BOSS_WAVE_INTERVAL: usize = 5;
+
+/// Number of zones a boss is divided into, each of which must be destroyed
+const BOSS_ZONES: usize = 4;
+
+/// Hits each boss zone takes before it is destroyed
+const BOSS_ZONE_HEALTH: u32 = 5;
+
+/// Points for destroying a boss
+const BOSS_POINTS: u64 = 1000;
+
+/// How quickly a boss rotates, in radians per second
+const BOSS_SPIN: Float = 0.3;
+
+/// How long the pause menu must be left alone before the attract demo starts, in seconds
+const DEMO_IDLE_SECONDS: u64 = 10;
+
+/// Most energy a player can store for firing
+const MAX_ENERGY: Float = 100.0;
+
+/// How long a player's energy takes to regenerate from empty to full, in seconds
+const ENERGY_REGEN_SECONDS: Float = 2.0;
+
+/// Energy used by a charged shot
+const CHARGED_SHOT_ENERGY: Float = 30.0;
+
+/// How far a laser beam reaches, in pixels
+const LASER_RANGE: Float = 300.0;
+
+/// Time between hits of a held laser beam, in seconds
+const LASER_COOLDOWN_SECONDS: Float = 0.2;
+
+/// Time after a hyperspace jump before the player can jump again, in seconds
+const HYPERSPACE_COOLDOWN_SECONDS: Float = 3.0;
+
+/// How long a ship takes to grow back to full size after a hyperspace jump, in seconds
+const HYPERSPACE_WARP_SECONDS: Float = 0.3;
+
+/// How long debris from asteroids lasts, in frames
+const DEBRIS_TTL: u64 = 30;
+
+/// How long the wreckage of a destroyed ship lasts, in frames
+const SHIP_DEBRIS_TTL: u64 = 60;
+
+/// How many sparks a bullet leaves where it hits something
+const SPARKS_PER_HIT: usize = 3;
+
+/// How long the sparks of a bullet hit last, in frames
+const SPARK_TTL: u64 = 12;
+
+/// New asteroids start at least this far from every player, where possible, in pixels
+const SAFE_SPAWN_DISTANCE: Float = 200.0;
+
+/// How many positions to try before settling for one closer than `SAFE_SPAWN_DISTANCE`
+const SAFE_SPAWN_ATTEMPTS: usize = 20;
+
+/// How many asteroids a piercing bullet passes through before it is spent
+const PIERCE_COUNT: u32 = 3;
+
+/// After piercing an asteroid, the bullet ignores asteroids for this many frames,
+/// so it doesn't immediately hit the children of the asteroid it just split.
+const PIERCE_INTANGIBLE_FRAMES: u64 = 24;
+
+/// How long an asteroid flashes after a hit that doesn't split it, in seconds
+const HIT_FLASH_SECONDS: Float = 0.1;
+
+/// How long asteroids spawned with a wave can't collide with anything, in seconds
+const SPAWN_PROTECTION_SECONDS: Float = 1.0;
+
+/// Seconds added to the time attack clock for each asteroid destroyed
+const TIME_BONUS_SECONDS: Float = 0.5;
+
+/// Seconds between asteroids when survival mode starts
+const SURVIVAL_START_INTERVAL: Float = 4.0;
+
+/// The time between survival asteroids halves every this many seconds
+const SURVIVAL_HALVING_SECONDS: Float = 60.0;
+
+/// The shortest time between survival asteroids, in seconds
+const SURVIVAL_MIN_INTERVAL: Float = 0.5;
+
+/// Survival asteroids get as big and fast as the next wave's every this many seconds
+const SURVIVAL_SECONDS_PER_WAVE: Float = 30.0;
+
+/// Survival mode stops spawning asteroids while there are this many
+const SURVIVAL_MAX_ASTEROIDS: usize = 30;
+
+/// With `--dampener`, ships left alone this long start slowing down, in seconds
+const DAMPENER_DELAY_SECONDS: Float = 0.5;
+
+/// Fraction of its speed a dampened ship loses each frame, instead of the usual drag
+const DAMPENER_DRAG: Float = 0.05;
+
+/// The pieces of a split asteroid start turned up to this far either way from it, in radians
+const SPLIT_ROTATION_JITTER: Float = 0.2;
+
+/// Velocity vectors, toggled with F5, reach where things will be this many frames from now
+const VELOCITY_VECTOR_FRAMES: Float = 10.0;
+
+/// How far inside the screen edge to draw arrows at things about to come on-screen, in pixels
+const EDGE_INDICATOR_MARGIN: Float = 16.0;
+
+/// Craters drawn inside each asteroid with `--craters`
+const CRATERS_PER_ASTEROID: usize = 2;
+
+/// How long the `--big-bang` intro runs before the players take control, in seconds
+const BIG_BANG_SECONDS: Float = 2.0;
+
+#[derive(Default, Clone, Copy)]
+struct Body {
+    position: Vector,
+    /// Where this was before the last step, for drawing trails
+    prev_position: Vector,
+    velocity: Vector,
+    /// in radians, clockwise from north
+    rotation: Float,
+    has_drag: bool,
+    accelerating: bool,
+    /// Both turning flags may be set at once, in which case they cancel out
+    turning_left: bool,
+    turning_right: bool,
+}
+
+type Verts = Either<&'static [Vector], Arc<[Vector]>>;
+type Triangles = Either<&'static [[Vector; 3]], Arc<[[Vector; 3]]>>;
+
+#[derive(Clone)]
+struct Polygon {
+    /// Offsets from origin, cyclic
+    verts: Verts,
+}
+
+#[derive(Clone)]
+enum Bounding {
+    /// This bounding box consists of N triangles, each with one vertex at the origin,
+    /// and two others consecutive elements of the cyclic list `verts`.
+    CyclicTriangles { verts: Verts },
+    /// This bounding box consists of N triangles, each explicitly listed.
+    Triangles { triangles: Triangles },
+}
+
+/// Why some verts can't be a `Bounding::CyclicTriangles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoundingError {
+    /// Fewer than 3 verts, which can't enclose anything
+    TooFewVerts(usize),
+    /// All the verts lie on one line, so every triangle is degenerate
+    Collinear,
+}
+
+impl Bounding {
+    /// `Bounding::CyclicTriangles` around `verts`, if they enclose some area.
+    fn cyclic(verts: Verts) -> Result<Bounding, BoundingError> {
+        if verts.len() < 3 {
+            return Err(BoundingError::TooFewVerts(verts.len()));
+        }
+        let first = verts[0];
+        let Some(&second) = verts.iter().find(|&&vert| vert != first) else {
+            return Err(BoundingError::Collinear);
+        };
+        if verts
+            .iter()
+            .all(|&vert| (second - first).perp_dot(vert - first) == 0.0)
+        {
+            return Err(BoundingError::Collinear);
+        }
+        Ok(Bounding::CyclicTriangles { verts })
+    }
+}
+
+#[derive(Clone)]
+enum WrappingBehavior {
+    Yes,
+    /// Wrapping should change to `Yes` once this entity is entirely on-screen,
+    /// and the entity moves freely off the edges until then.
+    OnceOnScreen,
+    /// This entity bounces off the screen edges instead of crossing them.
+    Bounce,
+}
+
+/// The id of a player's entity.
+type PlayerId = u64;
+
+#[derive(Clone)]
+struct Entity {
+    /// Unique among all entities, so an entity can be found again after others are removed.
+    id: u64,
+    body: Body,
+    /// Should drawing and moving this entity wrap around the screen.
+    wrap: WrappingBehavior,
+    sprite_verts: Option<Polygon>,
+    /// Lines drawn inside the sprite with `--craters`, as offsets from the origin like its verts
+    sprite_details: Vec<(Vector, Vector)>,
+    bounding: Option<Bounding>,
+    /// Multiplies the size of both the sprite and the bounding triangles.
+    scale: Float,
+    kind: EntityKind,
+    /// Called with the body and `age` each step before it moves, for bespoke motion
+    on_step: Option<fn(&mut Body, u64)>,
+    /// Steps taken since this entity was created
+    age: u64,
+    /// Drawn in this instead of the theme's color for its kind, if set
+    color: Option<Color>,
+}
+
+/// Builds an `Entity`, by default at rest at the origin, wrapping, unscaled,
+/// and with no sprite or bounding.
+struct EntityBuilder {
+    entity: Entity,
+}
+
+impl Entity {
+    fn builder(kind: EntityKind) -> EntityBuilder {
+        EntityBuilder {
+            entity: Entity {
+                id: 0,
+                body: Body::default(),
+                wrap: WrappingBehavior::Yes,
+                sprite_verts: None,
+                sprite_details: vec![],
+                bounding: None,
+                scale: 1.0,
+                kind,
+                on_step: None,
+                age: 0,
+                color: None,
+            },
+        }
+    }
+}
+
+impl EntityBuilder {
+    fn body(mut self, body: Body) -> Self {
+        self.entity.body = body;
+        self
+    }
+
+    fn sprite(mut self, verts: Verts) -> Self {
+        self.entity.sprite_verts = Some(Polygon { verts });
+        self
+    }
+
+    fn bounding(mut self, bounding: Bounding) -> Self {
+        self.entity.bounding = Some(bounding);
+        self
+    }
+
+    /// Uses `verts` as both the sprite and the bounding polygon.
+    fn shape(self, verts: Verts) -> Self {
+        self.sprite(verts.clone())
+            .bounding(Bounding::CyclicTriangles { verts })
+    }
+
+    fn scale(mut self, scale: Float) -> Self {
+        self.entity.scale = scale;
+        self
+    }
+
+    fn on_step(mut self, on_step: fn(&mut Body, u64)) -> Self {
+        self.entity.on_step = Some(on_step);
+        self
+    }
+
+    fn color(mut self, color: Option<Color>) -> Self {
+        self.entity.color = color;
+        self
+    }
+
+    /// Gives the entity a fresh id.
+    fn build(self) -> Entity {
+        Entity {
+            id: next_entity_id(),
+            ..self.entity
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EntityKind {
+    Asteroid {
+        /// Decremented by 1 each time the asteroid splits, until it is gone.
+        size: usize,
+        /// Hits left before it splits. Only the largest asteroids take more than one, on some
+        /// difficulties.
+        hits_remaining: u32,
+        /// Frames left of the flash after a hit that didn't split it
+        flash: u64,
+        /// Frames left before it can collide with anything, after spawning with a wave
+        spawn_protection: u64,
+    },
+    /// A huge asteroid that takes several hits to each of its zones to destroy, instead of splitting.
+    Boss {
+        /// Hits left before each zone is destroyed. Zone `n` is bounding triangles `2n` and `2n + 1`.
+        zones: [u32; BOSS_ZONES],
+    },
+    Bullet {
+        /// The player who fired this bullet
+        owner: PlayerId,
+        /// Time to live, in frames
+        ttl: u64,
+        /// Charged bullets vaporize asteroids instead of splitting them.
+        charged: bool,
+        /// How many more asteroids this bullet can pass through without being removed.
+        pierces: u32,
+        /// Frames until this bullet can hit asteroids again after piercing one.
+        intangible: u64,
+        /// How many more times this bullet can bounce off the screen edges,
+        /// if it bounces instead of wrapping.
+        bounces: u32,
+    },
+    Debris {
+        /// Time to live, in frames
+        ttl: u64,
+        /// The time to live it started with, in frames, for fading out
+        lifetime: u64,
+    },
+    /// Collected by flying a player into it.
+    Powerup {
+        /// Time to live, in frames
+        ttl: u64,
+        effect: PowerupEffect,
+    },
+    /// An expanding ring that destroys the asteroids it passes over.
+    Shockwave {
+        /// In pixels
+        radius: Float,
+        /// The shockwave disappears once its radius reaches this, in pixels.
+        max_radius: Float,
+        /// How much the radius increases each frame, in pixels.
+        growth: Float,
+    },
+    Player {
+        fire: Option<Keycode>,
+        /// Hold to charge, release to fire a charged shot.
+        charge: Option<Keycode>,
+        accelerate: Option<Keycode>,
+        turn_left: Option<Keycode>,
+        turn_right: Option<Keycode>,
+        /// Frames the charge key has been held, if it is held.
+        charge_frames: Option<u64>,
+        cycle_weapon: Option<Keycode>,
+        weapon: Weapon,
+        /// Remaining lives, including the current one.
+        lives: u32,
+        /// Where this player respawns after dying.
+        spawn_position: Vector,
+        /// Frames until this player can be killed again.
+        invulnerable: u64,
+        /// Frames until this player can thrust again after bumping into an asteroid.
+        stunned: u64,
+        /// Frames until this player can fire again.
+        cooldown: u64,
+        /// Frames since this player last thrusted or turned.
+        idle_frames: u64,
+        /// Whether the fire key is held.
+        firing: bool,
+        /// Spent by firing, and regenerates over time. Up to `MAX_ENERGY`.
+        energy: Float,
+        /// Flown by `autopilot::cpu_input` instead of by keys.
+        cpu: bool,
+        /// Destroys every asteroid on screen, using up one of `bombs`.
+        bomb: Option<Keycode>,
+        bombs: u32,
+        /// Set when the bomb key is pressed, until the main loop sets the bomb off.
+        detonating: bool,
+        /// Hold to pull powerups in front of the ship toward it.
+        magnet: Option<Keycode>,
+        magnet_active: bool,
+        /// Jumps the ship to a random spot on the screen.
+        hyperspace: Option<Keycode>,
+        /// Frames until this player can jump through hyperspace again.
+        hyperspace_cooldown: u64,
+        /// Set when the hyperspace key is pressed, until the main loop moves the ship.
+        jumping: bool,
+        /// Frames left of the ship growing back to full size after a jump.
+        warp: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PowerupEffect {
+    ExtraLife,
+    /// Sets off a shockwave around the player.
+    Bomb,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Weapon {
+    Standard,
+    /// Bullets pass through several asteroids, splitting each.
+    Piercing,
+    /// Fires several bullets at once in an arc, but needs time to reload.
+    Spread,
+    /// A beam that hits the first asteroid in front of the ship while fire is held.
+    Laser,
+}
+
+impl Weapon {
+    /// Energy used by each shot, or by each hit of a laser.
+    fn energy_cost(self) -> Float {
+        match self {
+            Weapon::Standard => 10.0,
+            Weapon::Piercing => 25.0,
+            Weapon::Spread => 40.0,
+            Weapon::Laser => 15.0,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Weapon::Standard => Weapon::Piercing,
+            Weapon::Piercing => Weapon::Spread,
+            Weapon::Spread => Weapon::Laser,
+            Weapon::Laser => Weapon::Standard,
+        }
+    }
+}
+
+const BULLET_VERTS: Verts = Either::Left(&[
+    Vector { x: 1.0, y: 3.0 },
+    Vector { x: 1.0, y: -3.0 },
+    Vector { x: -1.0, y: -3.0 },
+    Vector { x: -1.0, y: 3.0 },
+]);
+
+const BULLET_BOUNDS: Bounding = Bounding::Triangles {
+    triangles: Either::Left(&[
+        [
+            Vector { x: 1.0, y: 3.0 },
+            Vector { x: 1.0, y: -3.0 },
+            Vector { x: -1.0, y: -3.0 },
+        ],
+        [
+            Vector { x: 1.0, y: -3.0 },
+            Vector { x: -1.0, y: -3.0 },
+            Vector { x: -1.0, y: 3.0 },
+        ],
+    ]),
+};
+
+const CHARGED_BULLET_VERTS: Verts = Either::Left(&[
+    Vector { x: 2.0, y: 5.0 },
+    Vector { x: 2.0, y: -5.0 },
+    Vector { x: -2.0, y: -5.0 },
+    Vector { x: -2.0, y: 5.0 },
+]);
+
+const CHARGED_BULLET_BOUNDS: Bounding = Bounding::Triangles {
+    triangles: Either::Left(&[
+        [
+            Vector { x: 2.0, y: 5.0 },
+            Vector { x: 2.0, y: -5.0 },
+            Vector { x: -2.0, y: -5.0 },
+        ],
+        [
+            Vector { x: 2.0, y: -5.0 },
+            Vector { x: -2.0, y: -5.0 },
+            Vector { x: -2.0, y: 5.0 },
+        ],
+    ]),
+};
+
+const POWERUP_VERTS: Verts = Either::Left(&[
+    Vector { x: 0.0, y: -8.0 },
+    Vector { x: 8.0, y: 0.0 },
+    Vector { x: 0.0, y: 8.0 },
+    Vector { x: -8.0, y: 0.0 },
+]);
+
+/// An octagon, so each of the `BOSS_ZONES` zones is two of its triangles
+const BOSS_VERTS: Verts = Either::Left(&[
+    Vector { x: 0.0, y: -80.0 },
+    Vector { x: 56.6, y: -56.6 },
+    Vector { x: 80.0, y: 0.0 },
+    Vector { x: 56.6, y: 56.6 },
+    Vector { x: 0.0, y: 80.0 },
+    Vector { x: -56.6, y: 56.6 },
+    Vector { x: -80.0, y: 0.0 },
+    Vector { x: -56.6, y: -56.6 },
+]);
+
+/// Given to players in order when there is more than one, along with their bullets, so everyone
+/// can tell whose shots are whose. From the Okabe-Ito palette, which stays distinguishable with
+/// color blindness.
+const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::RGB(230, 159, 0),
+    Color::RGB(86, 180, 233),
+    Color::RGB(0, 158, 115),
+    Color::RGB(240, 228, 66),
+];
+
+/// Given to players in order, so each looks different.
+///
+/// Every hull's nose is at the same place, where bullets are fired from. Each must be visible
+/// in full from the origin, since it is also the player's `Bounding::CyclicTriangles`.
+const SHIP_HULLS: [Verts; MAX_PLAYERS] = [
+    // Arrowhead
+    Either::Left(&[
+        Vector { x: 0.0, y: -20.0 },
+        Vector { x: 10.0, y: 10.0 },
+        Vector { x: 0.0, y: 0.0 },
+        Vector { x: -10.0, y: 10.0 },
+    ]),
+    // Delta wing
+    Either::Left(&[
+        Vector { x: 0.0, y: -20.0 },
+        Vector { x: 12.0, y: 10.0 },
+        Vector { x: -12.0, y: 10.0 },
+    ]),
+    // Dart with swept fins
+    Either::Left(&[
+        Vector { x: 0.0, y: -20.0 },
+        Vector { x: 4.0, y: -4.0 },
+        Vector { x: 10.0, y: 12.0 },
+        Vector { x: 0.0, y: 6.0 },
+        Vector { x: -10.0, y: 12.0 },
+        Vector { x: -4.0, y: -4.0 },
+    ]),
+    // Blunt pentagon
+    Either::Left(&[
+        Vector { x: 0.0, y: -20.0 },
+        Vector { x: 10.0, y: -4.0 },
+        Vector { x: 8.0, y: 10.0 },
+        Vector { x: -8.0, y: 10.0 },
+        Vector { x: -10.0, y: -4.0 },
+    ]),
+];
+
+fn asteroid_verts(
+    vert_count: usize,
+    min_distance: Float,
+    max_distance: Float,
+    rng: &mut impl Rng,
+) -> Verts {
+    assert!(vert_count >= 3);
+    let theta_increment = consts::TAU / (vert_count as Float);
+    Either::Right(
+        (0..vert_count)
+            .map(|idx| {
+                rotation_matrix(theta_increment * idx as Float)
+                    * Vector {
+                        x: 0.0,
+                        y: rng.gen_range(min_distance..=max_distance),
+                    }
+            })
+            .collect(),
+    )
+}
+
+fn new_debris(body: Body, ttl: u64, rng: &mut impl Rng) -> Entity {
+    let verts = asteroid_verts(9, 2.0, 5.0, rng);
+    let bounding = Bounding::cyclic(verts.clone()).expect("asteroid verts surround the origin");
+    Entity::builder(EntityKind::Debris { ttl, lifetime: ttl })
+        .body(body)
+        .sprite(verts)
+        .bounding(bounding)
+        .build()
+}
+
+/// A plain bullet travelling with `body`, drawn in its owner's `color`, if it has one.
+fn new_bullet(body: Body, owner: PlayerId, color: Option<Color>, ttl: u64) -> Entity {
+    Entity::builder(EntityKind::Bullet {
+        owner,
+        ttl,
+        charged: false,
+        pierces: 0,
+        intangible: 0,
+        bounces: RICOCHET_BOUNCES,
+    })
+    .body(Body {
+        // Start without a trail
+        prev_position: body.position,
+        has_drag: false,
+        accelerating: false,
+        turning_left: false,
+        turning_right: false,
+        ..body
+    })
+    .sprite(BULLET_VERTS)
+    .bounding(BULLET_BOUNDS)
+    .color(color)
+    .build()
+}
+
+/// A bullet fired from the nose of `shooter`, as `owner` fires its weapons.
+fn fire_bullet(
+    shooter: &Body,
+    owner: PlayerId,
+    color: Option<Color>,
+    charged: bool,
+    pierces: u32,
+    settings: &Settings,
+) -> Entity {
+    let fire_direction = forward(shooter.rotation);
+    let mut bullet = new_bullet(
+        Body {
+            position: shooter.position + fire_direction * 20.0,
+            velocity: fire_direction * settings.physics.muzzle_speed + shooter.velocity,
+            rotation: shooter.rotation,
+            ..Default::default()
+        },
+        owner,
+        color,
+        BULLET_TTL,
+    );
+    bullet.scale = settings.physics.bullet_scale;
+    if charged {
+        bullet.sprite_verts = Some(Polygon {
+            verts: CHARGED_BULLET_VERTS,
+        });
+        bullet.bounding = Some(CHARGED_BULLET_BOUNDS);
+    }
+    if settings.ricochet {
+        bullet.wrap = WrappingBehavior::Bounce;
+    }
+    let EntityKind::Bullet {
+        charged: is_charged,
+        pierces: pierces_left,
+        ..
+    } = &mut bullet.kind
+    else {
+        unreachable!()
+    };
+    *is_charged = charged;
+    *pierces_left = pierces;
+    bullet
+}
+
+/// Rotations of each pellet of a spread shot relative to the shooter,
+/// evenly covering `angle` radians centered on the shooter's heading.
+fn spread_offsets(pellets: u32, angle: Float) -> impl Iterator<Item = Float> {
+    let step = if pellets > 1 {
+        angle / (pellets - 1) as Float
+    } else {
+        0.0
+    };
+    let first = -step * pellets.saturating_sub(1) as Float / 2.0;
+    (0..pellets).map(move |i| first + step * i as Float)
+}
+
+/// Returns where a laser fired by `shooter` starts and ends, and the index of the asteroid or
+/// boss it hits first along with which of its bounding triangles it hits, if any.
+///
+/// The beam stops at the first thing it hits, or at an edge that doesn't wrap. It carries on
+/// across edges that do, so its end may be off-screen. It passes through a boss's destroyed
+/// zones, and through asteroids that are still protected after spawning.
+fn laser_beam(
+    shooter: &Body,
+    entities: &[Entity],
+    bounds: Bounds,
+) -> (Vector, Vector, Option<(usize, usize)>) {
+    let fire_direction = forward(shooter.rotation);
+    let start = shooter.position + fire_direction * 20.0;
+    let mut range = LASER_RANGE;
+    for axis in 0..2 {
+        if !bounds.wraps(axis) && fire_direction[axis] != 0.0 {
+            let edge = if fire_direction[axis] > 0.0 {
+                bounds.size()[axis]
+            } else {
+                0.0
+            };
+            range = range.min(((edge - start[axis]) / fire_direction[axis]).max(0.0));
+        }
+    }
+    let end = start + fire_direction * range;
+    let hit = entities
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entity)| {
+            let triangles: Vec<(usize, [Vector; 3])> = match entity.kind {
+                EntityKind::Asteroid {
+                    spawn_protection: 0,
+                    ..
+                } => entity.bounding_triangles().enumerate().collect(),
+                EntityKind::Boss { zones } => entity
+                    .bounding_triangles()
+                    .enumerate()
+                    .filter(|&(triangle, _)| zones[triangle / 2] > 0)
+                    .collect(),
+                _ => return None,
+            };
+            let reach = triangles
+                .iter()
+                .flat_map(|(_, triangle)| triangle)
+                .map(|&vert| (vert - entity.body.position).length())
+                .fold(0.0, Float::max);
+            // Test every copy of the entity that the beam could reach across the wrapped edges
+            bounds
+                .wrap_offsets(start.min(end) - reach, start.max(end) + reach)
+                .flat_map(|offset| {
+                    triangles
+                        .iter()
+                        .filter_map(move |&(triangle_index, triangle)| {
+                            geometry::segment_triangle_intersection(
+                                start,
+                                end,
+                                triangle.map(|vert| vert - offset),
+                            )
+                            .map(|t| (t, triangle_index))
+                        })
+                })
+                .min_by(|(t1, _), (t2, _)| t1.total_cmp(t2))
+                .map(|(t, triangle)| (t, index, triangle))
+        })
+        .min_by(|(t1, ..), (t2, ..)| t1.total_cmp(t2));
+    match hit {
+        Some((t, index, triangle)) => (start, start.lerp(end, t), Some((index, triangle))),
+        None => (start, end, None),
+    }
+}
+
+/// Spends `cost` energy if there is enough, returning whether it was spent.
+fn spend_energy(energy: &mut Float, cost: Float) -> bool {
+    if *energy >= cost {
+        *energy -= cost;
+        true
+    } else {
+        false
+    }
+}
+
+/// Uses up one of a piercing bullet's pierces, returning a copy of it to split with.
+fn pierce(bullet: &mut Entity) -> Entity {
+    let EntityKind::Bullet {
+        pierces,
+        intangible,
+        ..
+    } = &mut bullet.kind
+    else {
+        unreachable!()
+    };
+    *pierces -= 1;
+    *intangible = PIERCE_INTANGIBLE_FRAMES;
+    bullet.clone()
+}
+
+fn new_powerup(body: Body, effect: PowerupEffect, ttl: u64) -> Entity {
+    Entity::builder(EntityKind::Powerup { ttl, effect })
+        .body(Body {
+            has_drag: false,
+            accelerating: false,
+            turning_left: false,
+            turning_right: false,
+            ..body
+        })
+        .shape(POWERUP_VERTS)
+        .on_step(wobble)
+        .build()
+}
+
+/// Rocks a powerup back and forth, so it catches the eye.
+fn wobble(body: &mut Body, age: u64) {
+    body.rotation = POWERUP_WOBBLE * (age as Float * 0.1).sin();
+}
+
+/// Applies a powerup's effect to the player collecting it, returning any entities it creates.
+fn collect_powerup(player: &mut Entity, effect: PowerupEffect) -> Vec<Entity> {
+    let EntityKind::Player { lives, .. } = &mut player.kind else {
+        unreachable!()
+    };
+    match effect {
+        PowerupEffect::ExtraLife => {
+            *lives += 1;
+            vec![]
+        }
+        PowerupEffect::Bomb => vec![new_shockwave(player.body.position)],
+    }
+}
+
+fn new_shockwave(position: Vector) -> Entity {
+    // A circle of radius 1, scaled up to the shockwave's radius as it grows
+    let verts = (0..SHOCKWAVE_SEGMENTS)
+        .map(|i| {
+            let angle = i as Float * consts::TAU / SHOCKWAVE_SEGMENTS as Float;
+            Vector::new(angle.cos(), angle.sin())
+        })
+        .collect();
+    Entity::builder(EntityKind::Shockwave {
+        radius: 0.0,
+        max_radius: SHOCKWAVE_MAX_RADIUS,
+        growth: SHOCKWAVE_GROWTH,
+    })
+    .body(Body {
+        position,
+        ..Default::default()
+    })
+    .sprite(Either::Right(verts))
+    .scale(0.0)
+    .build()
+}
+
+/// Returns the acceleration a player's magnet applies to a powerup,
+/// or `None` if the powerup is outside the magnet's cone.
+fn magnet_pull(
+    player_position: Vector,
+    player_rotation: Float,
+    powerup_position: Vector,
+    bounds: Bounds,
+) -> Option<Vector> {
+    let to_player = toroidal_delta(powerup_position, player_position, bounds);
+    let distance = to_player.length();
+    if distance > MAGNET_RADIUS || distance == 0.0 {
+        return None;
+    }
+    let heading = forward(player_rotation);
+    let to_powerup = -to_player / distance;
+    if to_powerup.dot(heading) < MAGNET_HALF_ANGLE.cos() {
+        return None;
+    }
+    Some(to_player / distance * MAGNET_STRENGTH)
+}
+
+fn new_asteroid(
+    size: usize,
+    body: Body,
+    shapes: &AsteroidShapeTable,
+    rng: &mut impl Rng,
+) -> Entity {
+    let shape = shapes.shape(size);
+    let (verts, radius) = match shapes.templates(size) {
+        [] => (
+            asteroid_verts(
+                shape.vert_count,
+                shape.min_distance,
+                shape.max_distance,
+                rng,
+            ),
+            shape.min_distance,
+        ),
+        templates => {
+            let verts = templates[rng.gen_range(0..templates.len())].clone();
+            let radius = verts
+                .iter()
+                .map(|vert| vert.length())
+                .fold(Float::INFINITY, Float::min);
+            (Either::Right(verts), radius)
+        }
+    };
+    let bounding = Bounding::cyclic(verts.clone()).expect("asteroid verts surround the origin");
+    let mut asteroid = Entity::builder(EntityKind::Asteroid {
+        size,
+        hits_remaining: 1,
+        flash: 0,
+        spawn_protection: 0,
+    })
+    .body(body)
+    .sprite(verts)
+    .bounding(bounding)
+    .build();
+    asteroid.sprite_details = crater_lines(asteroid.id, radius);
+    asteroid
+}
+
+/// Returns the lines of a few craters inside an asteroid with no vert closer than `radius` to its
+/// center, always the same for the same `seed`.
+///
+/// Each crater is half of a small hexagon, open on one side.
+fn crater_lines(seed: u64, radius: Float) -> Vec<(Vector, Vector)> {
+    // Seeded separately, so the game's own random numbers are the same with or without craters
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut lines = vec![];
+    for _ in 0..CRATERS_PER_ASTEROID {
+        // Far enough inside that the crater never crosses the outline, even where an edge
+        // between two verts dips towards the center
+        let center = rotation_matrix(rng.gen_range(0.0..consts::TAU))
+            * Vector::new(0.0, rng.gen_range(0.0..0.5) * radius);
+        let crater_radius = rng.gen_range(0.15..0.25) * radius;
+        let facing = rng.gen_range(0.0..consts::TAU);
+        let points: Vec<Vector> = (0..=3)
+            .map(|i| {
+                center
+                    + rotation_matrix(facing + i as Float * consts::FRAC_PI_3)
+                        * Vector::new(0.0, crater_radius)
+            })
+            .collect();
+        lines.extend(points.iter().copied().tuple_windows::<(_, _)>());
+    }
+    lines
+}
+
+fn new_boss(body: Body) -> Entity {
+    Entity::builder(EntityKind::Boss {
+        zones: [BOSS_ZONE_HEALTH; BOSS_ZONES],
+    })
+    .body(body)
+    .shape(BOSS_VERTS)
+    .build()
+}
+
+/// Returns the area of an entity's sprite, taking its scale into account.
+fn sprite_area(entity: &Entity) -> Float {
+    entity.sprite_verts.as_ref().map_or(0.0, |sprite| {
+        geometry::polygon_area(&sprite.verts) * entity.scale * entity.scale
+    })
+}
+
+/// Returns the vertices on the boundaries between a boss's zones.
+fn verts_of_boss_zones() -> impl Iterator<Item = Vector> {
+    BOSS_VERTS.left().unwrap().iter().copied().step_by(2)
+}
+
+/// Returns which of a boss's intact zones contains `point`, if any.
+fn boss_zone_at(boss: &Entity, point: Vector, bounds: Bounds) -> Option<usize> {
+    let EntityKind::Boss { zones } = boss.kind else {
+        unreachable!()
+    };
+    // Test the copy of the point nearest to the boss, which may be across the wrapping seam
+    let point = boss.body.position + toroidal_delta(boss.body.position, point, bounds);
+    boss.bounding_triangles()
+        .position(|triangle| geometry::point_in_triangle(point, triangle))
+        .map(|triangle| triangle / 2)
+        .filter(|&zone| zones[zone] > 0)
+}
+
+/// Returns a random point just off a random edge of the screen, far enough out that an asteroid
+/// there is entirely off-screen, and at least `min_distance` from every player if possible.
+///
+/// If no such point is found after a few tries, returns the furthest from any player of the
+/// points tried.
+fn safe_spawn_position(
+    bounds: Bounds,
+    players: &[Vector],
+    min_distance: Float,
+    rng: &mut impl Rng,
+) -> Vector {
+    let size = bounds.size();
+    let margin = 60.0;
+    let clearance = |position: Vector| {
+        players
+            .iter()
+            .map(|&player| toroidal_delta(position, player, bounds).length())
+            .fold(Float::INFINITY, Float::min)
+    };
+    let mut best = (Float::NEG_INFINITY, Vector::ZERO);
+    for _ in 0..SAFE_SPAWN_ATTEMPTS {
+        let position = match rng.gen_range(0..4) {
+            0 => Vector::new(rng.gen_range(0.0..size.x), -margin),
+            1 => Vector::new(rng.gen_range(0.0..size.x), size.y + margin),
+            2 => Vector::new(-margin, rng.gen_range(0.0..size.y)),
+            _ => Vector::new(size.x + margin, rng.gen_range(0.0..size.y)),
+        };
+        let distance = clearance(position);
+        if distance >= min_distance {
+            return position;
+        }
+        if distance > best.0 {
+            best = (distance, position);
+        }
+    }
+    best.1
+}
+
+/// Gives an asteroid as many hits to split as `difficulty` says its size takes.
+fn toughen(asteroid: &mut Entity, difficulty: &Difficulty) {
+    let EntityKind::Asteroid {
+        size,
+        hits_remaining,
+        ..
+    } = &mut asteroid.kind
+    else {
+        unreachable!()
+    };
+    *hits_remaining = difficulty.asteroid_hits(*size);
+}
+
+/// Makes a newly spawned asteroid blink and pass through everything for a moment, so it can't
+/// kill a player the instant it appears. Anything else is left alone.
+fn protect_spawn(entity: &mut Entity, settings: &Settings) {
+    if let EntityKind::Asteroid {
+        spawn_protection, ..
+    } = &mut entity.kind
+    {
+        *spawn_protection = settings.frames(SPAWN_PROTECTION_SECONDS);
+    }
+}
+
+/// Spawns an asteroid just off an edge, away from `players` if possible, heading on-screen.
+fn spawn_asteroid(
+    size: usize,
+    speed_multiplier: Float,
+    shapes: &AsteroidShapeTable,
+    bounds: Bounds,
+    players: &[Vector],
+    rng: &mut impl Rng,
+) -> Entity {
+    let position = safe_spawn_position(bounds, players, SAFE_SPAWN_DISTANCE, rng);
+    // Aim somewhere in the middle of the screen, so the asteroid comes fully on-screen
+    let target = bounds.size() * Vector::new(rng.gen_range(0.25..0.75), rng.gen_range(0.25..0.75));
+    let speed = rng.gen_range(0.8..1.6) * speed_multiplier;
+    let mut asteroid = new_asteroid(
+        size,
+        Body {
+            position,
+            velocity: (target - position).normalize_or_zero() * speed,
+            rotation: rng.gen_range(0.0..consts::TAU),
+            ..Default::default()
+        },
+        shapes,
+        rng,
+    );
+    asteroid.wrap = WrappingBehavior::OnceOnScreen;
+    asteroid
+}
+
+/// Returns the ends of the line showing `body`'s velocity, from its position to where it will be
+/// `VELOCITY_VECTOR_FRAMES` from now.
+fn velocity_vector(body: &Body) -> (Vector, Vector) {
+    (
+        body.position,
+        body.position + body.velocity * VELOCITY_VECTOR_FRAMES,
+    )
+}
+
+/// Returns how many asteroids, including bosses, are left to destroy.
+///
+/// The pieces of split asteroids count, but debris, bullets and everything else don't.
+fn asteroids_remaining(entities: &[Entity]) -> usize {
+    entities
+        .iter()
+        .filter(|entity| {
+            matches!(
+                entity.kind,
+                EntityKind::Asteroid { .. } | EntityKind::Boss { .. }
+            )
+        })
+        .count()
+}
+
+/// Returns the seconds between asteroids in survival mode, once it has run for `elapsed` seconds.
+///
+/// Never increases as `elapsed` does.
+fn survival_spawn_interval(elapsed: Float) -> Float {
+    let halvings = elapsed.max(0.0) / SURVIVAL_HALVING_SECONDS;
+    (SURVIVAL_START_INTERVAL * Float::powf(0.5, halvings)).max(SURVIVAL_MIN_INTERVAL)
+}
+
+/// Spawns the asteroids for the given wave just off-screen, heading inwards.
+fn spawn_wave(
+    wave: usize,
+    difficulty: &Difficulty,
+    shapes: &AsteroidShapeTable,
+    bounds: Bounds,
+    players: &[Vector],
+    rng: &mut impl Rng,
+) -> Vec<Entity> {
+    let size = bounds.size();
+    let asteroid_size = difficulty.max_asteroid_size(wave);
+    let speed_multiplier = difficulty.asteroid_speed_multiplier(wave);
+    let mut entities = (0..difficulty.asteroid_count(wave))
+        .map(|_| {
+            let mut asteroid = spawn_asteroid(
+                asteroid_size,
+                speed_multiplier,
+                shapes,
+                bounds,
+                players,
+                rng,
+            );
+            toughen(&mut asteroid, difficulty);
+            asteroid
+        })
+        .collect::<Vec<_>>();
+    if wave.is_multiple_of(BOSS_WAVE_INTERVAL) {
+        // Drift slowly down from above the middle of the screen
+        let position = Vector::new(size.x / 2.0, -100.0);
+        let mut boss = new_boss(Body {
+            position,
+            velocity: Vector::new(0.0, 0.5 * speed_multiplier),
+            ..Default::default()
+        });
+        boss.wrap = WrappingBehavior::OnceOnScreen;
+        entities.push(boss);
+    }
+    entities
+}
+
+/// Kills a player, leaving debris behind.
+///
+/// If the player has lives left it respawns, otherwise the caller should remove it. Only the
+/// player is reset, so the asteroid field carries on as it was, as in the arcade game.
+fn kill_player(
+    player: &mut Entity,
+    settings: &Settings,
+    rng: &mut impl Rng,
+) -> (StepResult, Vec<Entity>) {
+    let debris = (0..8)
+        .map(|_| {
+            let debris_direction = rng.gen_range(0.0..=consts::TAU);
+            let mut body = player.body;
+            body.velocity += rotation_matrix(debris_direction) * Vector { x: 0.0, y: 3.0 };
+            new_debris(body, SHIP_DEBRIS_TTL, rng)
+        })
+        .collect();
+    let EntityKind::Player {
+        lives,
+        spawn_position,
+        invulnerable,
+        charge_frames,
+        ..
+    } = &mut player.kind
+    else {
+        unreachable!()
+    };
+    *lives = lives.saturating_sub(1);
+    if *lives == 0 {
+        return (StepResult::RemoveEntity(RemoveReason::Destroyed), debris);
+    }
+    *invulnerable = settings.frames(RESPAWN_INVULNERABLE_SECONDS);
+    *charge_frames = None;
+    player.body = Body {
+        position: *spawn_position,
+        has_drag: player.body.has_drag,
+        ..Default::default()
+    };
+    (StepResult::None, debris)
+}
+
+/// In bumper mode, knocks a player away from an asteroid it bumped into, and stuns it briefly.
+fn bump_player(
+    player: &mut Entity,
+    asteroid_position: Vector,
+    bounds: Bounds,
+    settings: &Settings,
+) {
+    let EntityKind::Player { stunned, .. } = &mut player.kind else {
+        unreachable!()
+    };
+    *stunned = settings.frames(BUMPER_STUN_SECONDS);
+    let away = toroidal_delta(asteroid_position, player.body.position, bounds).normalize_or_zero();
+    player.body.velocity += away * BUMPER_KNOCKBACK;
+}
+
+/// Reflects debris off an asteroid at `asteroid_position`, if it is heading towards it.
+///
+/// Only the debris is affected, so the asteroid carries on as if nothing had hit it.
+fn bounce_debris(debris: &mut Entity, asteroid_position: Vector, bounds: Bounds) {
+    let normal =
+        toroidal_delta(asteroid_position, debris.body.position, bounds).normalize_or_zero();
+    let approach = debris.body.velocity.dot(normal);
+    if approach < 0.0 {
+        debris.body.velocity -= 2.0 * approach * normal;
+    }
+}
+
+/// Where and how deeply two entities overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Contact {
+    /// The middle of where the two overlap
+    point: Vector,
+    /// Unit vector from the first entity towards the second, along which they overlap least
+    normal: Vector,
+    /// How far the second entity would have to move along `normal` to stop overlapping, in pixels
+    depth: Float,
+}
+
+enum StepResult {
+    None,
+    RemoveEntity(RemoveReason),
+}
+
+/// Why an entity was removed, which decides what it leaves behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RemoveReason {
+    /// It ran out of time, bounces or lives, and just vanishes
+    Expired,
+    /// It was destroyed by a collision
+    Destroyed,
+    /// A player picked it up
+    Collected,
+}
+
+/// Returns what an entity leaves behind when it is removed for `reason`.
+///
+/// Bullets that hit something spark where they struck. Anything else that is destroyed leaves
+/// its own debris where it is destroyed, such as a split asteroid's or a ship's wreckage.
+fn death_effects(entity: &Entity, reason: RemoveReason, rng: &mut impl Rng) -> Vec<Entity> {
+    match (reason, &entity.kind) {
+        (RemoveReason::Destroyed, EntityKind::Bullet { .. }) => (0..SPARKS_PER_HIT)
+            .map(|_| {
+                let mut body = entity.body;
+                // Thrown back the way the bullet came, rather than carrying on through
+                body.velocity = rotation_matrix(rng.gen_range(-1.0..=1.0))
+                    * -body.velocity.normalize_or_zero()
+                    * rng.gen_range(1.0..3.0);
+                new_debris(body, SPARK_TTL, rng)
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// What should happen because two entities collided.
+///
+/// Collisions are all found before any are resolved, so events refer to entities by id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CollisionEvent {
+    AsteroidHitByBullet {
+        asteroid_id: u64,
+        bullet_id: u64,
+    },
+    BossHitByBullet {
+        boss_id: u64,
+        bullet_id: u64,
+    },
+    /// Only in bumper mode, where players bounce off asteroids instead of dying.
+    PlayerBumped {
+        player_id: u64,
+        asteroid_id: u64,
+    },
+    /// Only with `--bouncy-debris`.
+    DebrisBounced {
+        debris_id: u64,
+        asteroid_id: u64,
+    },
+    PlayerDied {
+        player_id: u64,
+    },
+    PlayerShot {
+        player_id: u64,
+        bullet_id: u64,
+    },
+    PowerupCollected {
+        player_id: u64,
+        powerup_id: u64,
+    },
+}
+
+/// Decides what should happen because `a` and `b` collided, if anything.
+fn collision_event(a: &Entity, b: &Entity, settings: &Settings) -> Option<CollisionEvent> {
+    ordered_collision_event(a, b, settings).or_else(|| ordered_collision_event(b, a, settings))
+}
+
+/// Like `collision_event`, but only recognizes the pair in the given order.
+fn ordered_collision_event(a: &Entity, b: &Entity, settings: &Settings) -> Option<CollisionEvent> {
+    match (a.kind, b.kind) {
+        (EntityKind::Asteroid { .. }, EntityKind::Bullet { .. }) => {
+            Some(CollisionEvent::AsteroidHitByBullet {
+                asteroid_id: a.id,
+                bullet_id: b.id,
+            })
+        }
+        (EntityKind::Boss { .. }, EntityKind::Bullet { .. }) => {
+            Some(CollisionEvent::BossHitByBullet {
+                boss_id: a.id,
+                bullet_id: b.id,
+            })
+        }
+        // In practice mode, nothing can kill a player
+        (
+            EntityKind::Player { .. },
+            EntityKind::Asteroid { .. } | EntityKind::Boss { .. } | EntityKind::Bullet { .. },
+        ) if settings.practice => None,
+        (EntityKind::Player { .. }, EntityKind::Asteroid { .. } | EntityKind::Boss { .. })
+            if settings.bumper =>
+        {
+            Some(CollisionEvent::PlayerBumped {
+                player_id: a.id,
+                asteroid_id: b.id,
+            })
+        }
+        (EntityKind::Player { .. }, EntityKind::Asteroid { .. } | EntityKind::Boss { .. }) => {
+            Some(CollisionEvent::PlayerDied { player_id: a.id })
+        }
+        (EntityKind::Player { .. }, EntityKind::Bullet { .. }) => {
+            Some(CollisionEvent::PlayerShot {
+                player_id: a.id,
+                bullet_id: b.id,
+            })
+        }
+        (EntityKind::Player { .. }, EntityKind::Powerup { .. }) => {
+            Some(CollisionEvent::PowerupCollected {
+                player_id: a.id,
+                powerup_id: b.id,
+            })
+        }
+        (EntityKind::Debris { .. }, EntityKind::Asteroid { .. }) => {
+            Some(CollisionEvent::DebrisBounced {
+                debris_id: a.id,
+                asteroid_id: b.id,
+            })
+        }
+        // TODO: Players colliding
+        _ => None,
+    }
+}
+
+impl Entity {
+    fn handle_event(&mut self, event: &Event, settings: &Settings) -> Vec<Entity> {
+        let mut new_entities = vec![];
+        match &mut self.kind {
+            EntityKind::Player {
+                fire,
+                charge,
+                accelerate,
+                turn_left,
+                turn_right,
+                charge_frames,
+                cycle_weapon,
+                weapon,
+                magnet,
+                magnet_active,
+                firing,
+                energy,
+                bomb,
+                bombs,
+                detonating,
+                hyperspace,
+                hyperspace_cooldown,
+                jumping,
+                ..
+            } => {
+                // Mirroring the screen mirrors which way the ship appears to turn
+                let (turn_left, turn_right) = if settings.invert_y {
+                    (turn_right, turn_left)
+                } else {
+                    (turn_left, turn_right)
+                };
+                // Each event carries one key, so keys held together arrive as separate events,
+                // each of which sets its own flag
+                match *event {
+                    Event::KeyDown {
+                        keycode: Some(keycode),
+                        repeat: false,
+                        ..
+                    } => {
+                        if Some(keycode) == *accelerate {
+                            self.body.accelerating = true;
+                        } else if Some(keycode) == *turn_left {
+                            self.body.turning_left = true;
+                        } else if Some(keycode) == *turn_right {
+                            self.body.turning_right = true;
+                        } else if Some(keycode) == *fire {
+                            *firing = true;
+                            new_entities.extend(self.fire(settings));
+                        } else if Some(keycode) == *charge {
+                            *charge_frames = Some(0);
+                        } else if Some(keycode) == *cycle_weapon {
+                            *weapon = weapon.next();
+                        } else if Some(keycode) == *magnet {
+                            *magnet_active = true;
+                        } else if Some(keycode) == *bomb && *bombs > 0 {
+                            *bombs -= 1;
+                            *detonating = true;
+                        } else if Some(keycode) == *hyperspace && *hyperspace_cooldown == 0 {
+                            *hyperspace_cooldown = settings.frames(HYPERSPACE_COOLDOWN_SECONDS);
+                            *jumping = true;
+                        }
+                    }
+                    Event::KeyUp {
+                        keycode: Some(keycode),
+                        ..
+                    } => {
+                        if Some(keycode) == *accelerate {
+                            self.body.accelerating = false;
+                        } else if Some(keycode) == *turn_left {
+                            self.body.turning_left = false;
+                        } else if Some(keycode) == *turn_right {
+                            self.body.turning_right = false;
+                        } else if Some(keycode) == *fire {
+                            *firing = false;
+                        } else if Some(keycode) == *charge {
+                            // Releasing early fizzles
+                            if charge_frames
+                                .take()
+                                .is_some_and(|frames| frames >= settings.frames(CHARGE_SECONDS))
+                                && spend_energy(energy, CHARGED_SHOT_ENERGY)
+                            {
+                                new_entities.push(fire_bullet(
+                                    &self.body, self.id, self.color, true, 0, settings,
+                                ));
+                            }
+                        } else if Some(keycode) == *magnet {
+                            *magnet_active = false;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            EntityKind::Asteroid { .. } => {}
+            EntityKind::Boss { .. } => {}
+            EntityKind::Bullet { .. } => {}
+            EntityKind::Debris { .. } => {}
+            EntityKind::Powerup { .. } => {}
+            EntityKind::Shockwave { .. } => {}
+        }
+        new_entities
+    }
+
+    /// Fires a player's current weapon, if it is ready.
+    fn fire(&mut self, settings: &Settings) -> Vec<Entity> {
+        let EntityKind::Player {
+            weapon,
+            cooldown,
+            energy,
+            ..
+        } = &mut self.kind
+        else {
+            unreachable!()
+        };
+        let mut new_entities = vec![];
+        match weapon {
+            // Fires each frame while held, in the main loop
+            Weapon::Laser => {}
+            _ if *cooldown > 0 => {}
+            _ if !spend_energy(energy, weapon.energy_cost()) => {}
+            Weapon::Standard => {
+                new_entities.push(fire_bullet(
+                    &self.body, self.id, self.color, false, 0, settings,
+                ));
+                if settings.auto_fire {
+                    *cooldown = settings.frames(AUTO_FIRE_COOLDOWN_SECONDS);
+                }
+            }
+            Weapon::Piercing => {
+                new_entities.push(fire_bullet(
+                    &self.body,
+                    self.id,
+                    self.color,
+                    false,
+                    PIERCE_COUNT,
+                    settings,
+                ));
+                if settings.auto_fire {
+                    *cooldown = settings.frames(AUTO_FIRE_COOLDOWN_SECONDS);
+                }
+            }
+            Weapon::Spread => {
+                for offset in spread_offsets(settings.spread_pellets, settings.spread_angle) {
+                    let barrel = Body {
+                        rotation: self.body.rotation + offset,
+                        ..self.body
+                    };
+                    new_entities.push(fire_bullet(
+                        &barrel, self.id, self.color, false, 0, settings,
+                    ));
+                }
+                *cooldown = settings.frames(SPREAD_COOLDOWN_SECONDS);
+            }
+        }
+        new_entities
+    }
+
+    fn step(&mut self, bounds: Bounds, settings: &Settings) -> StepResult {
+        let stunned = matches!(self.kind, EntityKind::Player { stunned: 1.., .. });
+        if self.body.accelerating && !stunned {
+            self.body.velocity += forward(self.body.rotation) * settings.physics.thrust;
+        }
+        // Holding both turn keys doesn't turn either way
+        match (self.body.turning_left, self.body.turning_right) {
+            (false, true) => {
+                self.body.rotation = (self.body.rotation
+                    - settings.physics.turn_rate(settings.fps))
+                .rem_euclid(consts::TAU);
+            }
+            (true, false) => {
+                self.body.rotation = (self.body.rotation + settings.physics.turn_rate(settings.fps))
+                    .rem_euclid(consts::TAU)
+            }
+            _ => {}
+        }
+
+        if self.body.has_drag {
+            let mut drag = settings.physics.drag;
+            if let EntityKind::Player { idle_frames, .. } = self.kind {
+                if settings.dampener && idle_frames >= settings.frames(DAMPENER_DELAY_SECONDS) {
+                    drag = drag.max(DAMPENER_DRAG);
+                }
+            }
+            self.body.velocity *= 1.0 - drag;
+        }
+        // Debris slows as it fades, however ships handle
+        if let EntityKind::Debris { .. } = self.kind {
+            self.body.velocity *= 1.0 - settings.debris_drag;
+        }
+
+        if let Some(on_step) = self.on_step {
+            on_step(&mut self.body, self.age);
+        }
+        self.age += 1;
+
+        self.body.prev_position = self.body.position;
+        self.body.position += self.body.velocity;
+        match self.wrap {
+            WrappingBehavior::Yes => {
+                self.body.position = bounds.wrap_position(self.body.position);
+                for axis in 0..2 {
+                    if !bounds.wraps(axis) {
+                        bounce_on_axis(&mut self.body, axis, bounds);
+                    }
+                }
+            }
+            WrappingBehavior::OnceOnScreen => {
+                let (min, max) = self.bounding_box();
+                if bounds.contains_aabb(min, max) {
+                    self.wrap = WrappingBehavior::Yes;
+                }
+            }
+            WrappingBehavior::Bounce => {
+                let mut bounced = false;
+                for axis in 0..2 {
+                    bounced |= bounce_on_axis(&mut self.body, axis, bounds);
+                }
+                if bounced {
+                    // Keep the sprite pointing the way it's going
+                    self.body.rotation = rotation_towards(self.body.velocity);
+                    if let EntityKind::Bullet { bounces, .. } = &mut self.kind {
+                        match bounces.checked_sub(1) {
+                            Some(new_bounces) => *bounces = new_bounces,
+                            None => return StepResult::RemoveEntity(RemoveReason::Expired),
+                        }
+                    }
+                }
+            }
+        }
+        match &mut self.kind {
+            EntityKind::Asteroid {
+                flash,
+                spawn_protection,
+                ..
+            } => {
+                *flash = flash.saturating_sub(1);
+                *spawn_protection = spawn_protection.saturating_sub(1);
+            }
+            EntityKind::Boss { .. } => {
+                self.body.rotation = (self.body.rotation + BOSS_SPIN / settings.fps as Float)
+                    .rem_euclid(consts::TAU);
+            }
+            EntityKind::Bullet {
+                ttl, intangible, ..
+            } => {
+                *intangible = intangible.saturating_sub(1);
+                match ttl.checked_sub(1) {
+                    Some(new_ttl) => *ttl = new_ttl,
+                    None => return StepResult::RemoveEntity(RemoveReason::Expired),
+                }
+            }
+            EntityKind::Shockwave {
+                radius,
+                max_radius,
+                growth,
+            } => {
+                *radius += *growth;
+                if *radius >= *max_radius {
+                    return StepResult::RemoveEntity(RemoveReason::Expired);
+                }
+                self.scale = *radius;
+            }
+            EntityKind::Debris { ttl, .. } | EntityKind::Powerup { ttl, .. } => {
+                match ttl.checked_sub(1) {
+                    Some(new_ttl) => *ttl = new_ttl,
+                    None => return StepResult::RemoveEntity(RemoveReason::Expired),
+                }
+            }
+            EntityKind::Player {
+                charge_frames,
+                invulnerable,
+                stunned,
+                cooldown,
+                idle_frames,
+                energy,
+                hyperspace_cooldown,
+                warp,
+                ..
+            } => {
+                let body = &self.body;
+                if body.accelerating || body.turning_left || body.turning_right {
+                    *idle_frames = 0;
+                } else {
+                    *idle_frames = idle_frames.saturating_add(1);
+                }
+                if let Some(frames) = charge_frames {
+                    *frames = frames.saturating_add(1);
+                }
+                *invulnerable = invulnerable.saturating_sub(1);
+                *stunned = stunned.saturating_sub(1);
+                *cooldown = cooldown.saturating_sub(1);
+                *hyperspace_cooldown = hyperspace_cooldown.saturating_sub(1);
+                // Grow back from nothing after a jump
+                *warp = warp.saturating_sub(1);
+                let warp_frames = settings.frames(HYPERSPACE_WARP_SECONDS).max(1);
+                self.scale = 1.0 - *warp as Float / warp_frames as Float;
+                let regen = MAX_ENERGY / settings.frames(ENERGY_REGEN_SECONDS) as Float;
+                *energy = (*energy + regen).min(MAX_ENERGY);
+            }
+        }
+        StepResult::None
+    }
+
+    fn bounding_triangles(&self) -> impl Iterator<Item = [Vector; 3]> + Clone + '_ {
+        // type Ret = Either<_, std::iter::Empty<T>>;
+        let Some(bounding) = &self.bounding else {
+            return Either::Right(Either::Right(std::iter::empty()));
+        };
+        let rota = rotation_matrix(self.body.rotation) * self.scale;
+        let origin = self.body.position;
+        match bounding {
+            Bounding::CyclicTriangles { verts } => {
+                // // https://github.com/rust-itertools/itertools/issues/685
+                // let triangles = verts
+                //     .iter()
+                //     .copied()
+                //     .circular_tuple_windows()
+                //     .map(move |(p1, p2)| [origin, origin + rota * p1, origin + rota * p2]);
+                let triangles = verts
+                    .iter()
+                    .cycle()
+                    .tuple_windows()
+                    .take(verts.len())
+                    .map(move |(&p1, &p2)| [origin, origin + rota * p1, origin + rota * p2]);
+
+                Either::Left(triangles)
+            }
+            Bounding::Triangles { triangles } => {
+                let triangles = triangles.iter().map(move |&[p1, p2, p3]| {
+                    [origin + rota * p1, origin + rota * p2, origin + rota * p3]
+                });
+
+                Either::Right(Either::Left(triangles))
+            }
+        }
+    }
+
+    /// Returns the minimum and maximum corners of the axis-aligned box around the bounding
+    /// triangles and position.
+    fn bounding_box(&self) -> (Vector, Vector) {
+        self.bounding_triangles()
+            .flatten()
+            .chain([self.body.position])
+            .fold(
+                (
+                    Vector::splat(Float::INFINITY),
+                    Vector::splat(Float::NEG_INFINITY),
+                ),
+                |(min, max), point| (min.min(point), max.max(point)),
+            )
+    }
+
+    /// Returns the bounding triangles moved across each wrapping edge they overlap, a whole
+    /// screen at a time, which is where wrap-aware collision also finds this entity.
+    fn phantom_triangles(&self, bounds: Bounds) -> impl Iterator<Item = [Vector; 3]> + '_ {
+        let offsets = match self.wrap {
+            WrappingBehavior::Yes => {
+                let (min, max) = self.bounding_box();
+                Either::Left(bounds.wrap_offsets(min, max))
+            }
+            _ => Either::Right(std::iter::empty()),
+        };
+        offsets
+            .filter(|&offset| offset != Vector::ZERO)
+            .flat_map(move |offset| {
+                self.bounding_triangles()
+                    .map(move |triangle| triangle.map(|point| point + offset))
+            })
+    }
+
+    /// Returns true if self and other may collide, i.e. if they do anything when they overlap.
+    fn collides_with(&self, other: &Self, settings: &Settings) -> bool {
+        match (self.kind, other.kind) {
+            (
+                EntityKind::Asteroid {
+                    spawn_protection: 1..,
+                    ..
+                },
+                _,
+            )
+            | (
+                _,
+                EntityKind::Asteroid {
+                    spawn_protection: 1..,
+                    ..
+                },
+            ) => false,
+            (EntityKind::Debris { .. }, EntityKind::Asteroid { .. })
+            | (EntityKind::Asteroid { .. }, EntityKind::Debris { .. }) => settings.bouncy_debris,
+            (EntityKind::Debris { .. }, _) | (_, EntityKind::Debris { .. }) => false,
+            (EntityKind::Asteroid { .. }, EntityKind::Asteroid { .. }) => false,
+            (EntityKind::Bullet { .. }, EntityKind::Bullet { .. }) => false,
+
+            (EntityKind::Bullet { intangible, .. }, EntityKind::Asteroid { .. })
+            | (EntityKind::Asteroid { .. }, EntityKind::Bullet { intangible, .. }) => {
+                intangible == 0
+            }
+            (EntityKind::Asteroid { .. }, EntityKind::Player { invulnerable, .. })
+            | (EntityKind::Player { invulnerable, .. }, EntityKind::Asteroid { .. }) => {
+                invulnerable == 0
+            }
+            // Players can't shoot themselves, e.g. by flying into their own bullets
+            (EntityKind::Bullet { owner, .. }, EntityKind::Player { invulnerable, .. }) => {
+                invulnerable == 0 && owner != other.id
+            }
+            (EntityKind::Player { invulnerable, .. }, EntityKind::Bullet { owner, .. }) => {
+                invulnerable == 0 && owner != self.id
+            }
+            (EntityKind::Player { .. }, EntityKind::Player { .. }) => true,
+            (EntityKind::Boss { .. }, EntityKind::Asteroid { .. } | EntityKind::Boss { .. })
+            | (EntityKind::Asteroid { .. }, EntityKind::Boss { .. }) => false,
+            (EntityKind::Bullet { intangible, .. }, EntityKind::Boss { .. })
+            | (EntityKind::Boss { .. }, EntityKind::Bullet { intangible, .. }) => intangible == 0,
+            (EntityKind::Boss { .. }, EntityKind::Player { invulnerable, .. })
+            | (EntityKind::Player { invulnerable, .. }, EntityKind::Boss { .. }) => {
+                invulnerable == 0
+            }
+            (EntityKind::Player { .. }, EntityKind::Powerup { .. })
+            | (EntityKind::Powerup { .. }, EntityKind::Player { .. }) => true,
+            (EntityKind::Powerup { .. }, _) | (_, EntityKind::Powerup { .. }) => false,
+            // Shockwaves destroy asteroids by distance, in the main loop
+            (EntityKind::Shockwave { .. }, _) | (_, EntityKind::Shockwave { .. }) => false,
+        }
+    }
+
+    /// Returns where and how deeply `self` and `other` overlap, if they do.
+    ///
+    /// The contact is with the copy of `other` nearest to `self`, which may be across the wrapping
+    /// seam.
+    fn collision(&self, other: &Self, bounds: Bounds) -> Option<Contact> {
+        // If both entities wrap, test against the copy of `other` nearest to `self`,
+        // which may be across the wrapping seam.
+        let other_offset = if matches!(self.wrap, WrappingBehavior::Yes)
+            && matches!(other.wrap, WrappingBehavior::Yes)
+        {
+            let direct = other.body.position - self.body.position;
+            toroidal_delta(self.body.position, other.body.position, bounds) - direct
+        } else {
+            Vector::ZERO
+        };
+        // Degenerate triangles have no inside to overlap, but can still have a bounding box that
+        // overlaps another triangle's, so they are skipped
+        let has_area = |&triangle: &[Vector; 3]| geometry::triangle_area(triangle) > 0.0;
+        for self_triangle in self.bounding_triangles().filter(has_area) {
+            // Simple fast-negative check
+            let (min_self_x, max_self_x, min_self_y, max_self_y) = self_triangle.iter().fold(
+                (
+                    Float::INFINITY,
+                    Float::NEG_INFINITY,
+                    Float::INFINITY,
+                    Float::NEG_INFINITY,
+                ),
+                |(min_self_x, max_self_x, min_self_y, max_self_y), &Vector { x, y }| {
+                    (
+                        x.min(min_self_x),
+                        x.max(max_self_x),
+                        y.min(min_self_y),
+                        y.max(max_self_y),
+                    )
+                },
+            );
+            for other_triangle in other.bounding_triangles().filter(has_area) {
+                let other_triangle = other_triangle.map(|point| point + other_offset);
+                // Simple fast-negative check
+                let (min_other_x, max_other_x, min_other_y, max_other_y) =
+                    other_triangle.iter().fold(
+                        (
+                            Float::INFINITY,
+                            Float::NEG_INFINITY,
+                            Float::INFINITY,
+                            Float::NEG_INFINITY,
+                        ),
+                        |(min_other_x, max_other_x, min_other_y, max_other_y), &Vector { x, y }| {
+                            (
+                                x.min(min_other_x),
+                                x.max(max_other_x),
+                                y.min(min_other_y),
+                                y.max(max_other_y),
+                            )
+                        },
+                    );
+
+                if max_self_x < min_other_x
+                    || min_self_x > max_other_x
+                    || max_self_y < min_other_y
+                    || min_self_y > max_other_y
+                {
+                    continue;
+                }
+                let Some((normal, depth)) =
+                    geometry::triangle_penetration(self_triangle, other_triangle)
+                else {
+                    continue;
+                };
+                let intersection = geometry::clip_to_triangle(&self_triangle, other_triangle);
+                if intersection.is_empty() {
+                    continue;
+                }
+                let point = intersection.iter().sum::<Vector>() / intersection.len() as Float;
+                return Some(Contact {
+                    point,
+                    normal,
+                    depth,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Returns a new id, unique among all entities ever created.
+fn next_entity_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns the index of the entity with the given id, if it still exists.
+fn index_of(entities: &[Entity], id: u64) -> Option<usize> {
+    entities.iter().position(|entity| entity.id == id)
+}
+
+/// Removes the entities at indices `a` and `b`, returning them in that order.
+fn remove_pair(entities: &mut Vec<Entity>, a: usize, b: usize) -> (Entity, Entity) {
+    // Remove the later one first, so removing it doesn't move the earlier one
+    if a > b {
+        let a = entities.swap_remove(a);
+        let b = entities.swap_remove(b);
+        (a, b)
+    } else {
+        let b = entities.swap_remove(b);
+        let a = entities.swap_remove(a);
+        (a, b)
+    }
+}
+
+pub fn shade(c: Color, by: Float) -> Color {
+    Color {
+        r: (c.r as Float * by) as u8,
+        g: (c.g as Float * by) as u8,
+        b: (c.b as Float * by) as u8,
+        a: c.a,
+    }
+}
+
+/// Blends from `a` at `t = 0` to `b` at `t = 1`, rounding each channel. `t` is clamped to that
+/// range.
+pub fn lerp_color(a: Color, b: Color, t: Float) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (a as Float + (b as Float - a as Float) * t).round() as u8;
+    Color {
+        r: lerp(a.r, b.r),
+        g: lerp(a.g, b.g),
+        b: lerp(a.b, b.b),
+        a: lerp(a.a, b.a),
+    }
+}
+
+/// Reflects a body that has crossed either edge of the play area along `axis` back inside,
+/// returning true if it did.
+fn bounce_on_axis(body: &mut Body, axis: usize, bounds: Bounds) -> bool {
+    let size = bounds.size()[axis];
+    if body.position[axis] < 0.0 {
+        body.position[axis] = -body.position[axis];
+        body.velocity[axis] = body.velocity[axis].abs();
+        true
+    } else if body.position[axis] > size {
+        body.position[axis] = 2.0 * size - body.position[axis];
+        body.velocity[axis] = -body.velocity[axis].abs();
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns the rotation something needs to face along `direction`.
+pub fn rotation_towards(direction: Vector) -> Float {
+    // Rotation 0 faces north, and increases clockwise
+    (-direction.x).atan2(-direction.y)
+}
+
+/// Returns the shortest vector from `a` to `b` on a torus the size of `bounds`,
+/// i.e. taking into account that the path may cross the wrapping seam.
+///
+/// Only edges that wrap are crossed.
+pub fn toroidal_delta(a: Vector, b: Vector, bounds: Bounds) -> Vector {
+    let mut delta = b - a;
+    let size = bounds.size();
+    for axis in 0..2 {
+        if bounds.wraps(axis) {
+            delta[axis] -= size[axis] * (delta[axis] / size[axis]).round();
+        }
+    }
+    delta
+}
+
+/// Returns the ends of the line drawn behind a moving body, from where it was last frame to
+/// where it is now.
+///
+/// If it crossed the wrapping seam, the start is placed off-screen beside the current position,
+/// so the line doesn't cross the whole screen.
+fn trail_segment(body: &Body, bounds: Bounds) -> (Vector, Vector) {
+    let position = body.position;
+    (
+        position + toroidal_delta(position, body.prev_position, bounds),
+        position,
+    )
+}
+
+/// Returns the candidate whose position is closest to `from`.
+///
+/// If `wrap` is true, distances are measured across the wrapping seam where that is shorter.
+fn nearest<'a>(
+    from: Vector,
+    candidates: impl Iterator<Item = &'a Entity>,
+    bounds: Bounds,
+    wrap: bool,
+) -> Option<&'a Entity> {
+    candidates.min_by_key(|candidate| {
+        let delta = if wrap {
+            toroidal_delta(from, candidate.body.position, bounds)
+        } else {
+            candidate.body.position - from
+        };
+        float_ord::FloatOrd(delta.length_squared())
+    })
+}
+
+/// Returns the point to aim at so that a bullet fired from `shooter` at `bullet_speed`
+/// meets a target at `target_pos` moving with constant `target_vel`,
+/// or `None` if the bullet can never catch the target.
+///
+/// All positions and velocities should be relative to the shooter's frame,
+/// i.e. with the shooter's own velocity subtracted, since bullets inherit it.
+pub fn lead_target(
+    shooter: Vector,
+    target_pos: Vector,
+    target_vel: Vector,
+    bullet_speed: Float,
+) -> Option<Vector> {
+    // Solve |offset + target_vel * t| = bullet_speed * t for the earliest t > 0
+    let offset = target_pos - shooter;
+    let a = target_vel.length_squared() - bullet_speed * bullet_speed;
+    let b = 2.0 * offset.dot(target_vel);
+    let c = offset.length_squared();
+    let t = if a.abs() < Float::EPSILON {
+        // Target and bullet have the same speed, so the quadratic degenerates
+        if b >= 0.0 {
+            return None;
+        }
+        -c / b
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt_discriminant = discriminant.sqrt();
+        let t1 = (-b - sqrt_discriminant) / (2.0 * a);
+        let t2 = (-b + sqrt_discriminant) / (2.0 * a);
+        match (t1 > 0.0, t2 > 0.0) {
+            (true, true) => t1.min(t2),
+            (true, false) => t1,
+            (false, true) => t2,
+            (false, false) => return None,
+        }
+    };
+    Some(target_pos + target_vel * t)
+}
+
+/// Returns the unit vector something with the given rotation faces along,
+/// which is north, `(0, -1)`, for rotation 0.
+pub fn forward(rotation: Float) -> Vector {
+    rotation_matrix(rotation) * Vector { x: 0.0, y: -1.0 }
+}
+
+pub fn rotation_matrix(theta: Float) -> Matrix {
+    Matrix::from_cols(
+        Vector {
+            x: theta.cos(),
+            y: -theta.sin(),
+        },
+        Vector {
+            x: theta.sin(),
+            y: theta.cos(),
+        },
+    )
+}
+
+/// The keys a human player flies with.
+struct ControlScheme {
+    accelerate: Keycode,
+    turn_left: Keycode,
+    turn_right: Keycode,
+    fire: Keycode,
+    charge: Keycode,
+    cycle_weapon: Keycode,
+    bomb: Keycode,
+    magnet: Keycode,
+    hyperspace: Keycode,
+}
+
+/// Given to human players in order.
+const CONTROL_SCHEMES: [ControlScheme; MAX_PLAYERS] = [
+    ControlScheme {
+        accelerate: Keycode::Up,
+        turn_left: Keycode::Left,
+        turn_right: Keycode::Right,
+        fire: Keycode::Space,
+        charge: Keycode::RShift,
+        cycle_weapon: Keycode::Slash,
+        bomb: Keycode::Comma,
+        magnet: Keycode::Period,
+        hyperspace: Keycode::Down,
+    },
+    ControlScheme {
+        accelerate: Keycode::W,
+        turn_left: Keycode::A,
+        turn_right: Keycode::D,
+        fire: Keycode::LCtrl,
+        charge: Keycode::LShift,
+        cycle_weapon: Keycode::Q,
+        bomb: Keycode::Z,
+        magnet: Keycode::E,
+        hyperspace: Keycode::S,
+    },
+    ControlScheme {
+        accelerate: Keycode::I,
+        turn_left: Keycode::J,
+        turn_right: Keycode::L,
+        fire: Keycode::B,
+        charge: Keycode::V,
+        cycle_weapon: Keycode::U,
+        bomb: Keycode::M,
+        magnet: Keycode::O,
+        hyperspace: Keycode::K,
+    },
+    ControlScheme {
+        accelerate: Keycode::Kp8,
+        turn_left: Keycode::Kp4,
+        turn_right: Keycode::Kp6,
+        fire: Keycode::Kp0,
+        charge: Keycode::KpEnter,
+        cycle_weapon: Keycode::KpPlus,
+        bomb: Keycode::KpMinus,
+        magnet: Keycode::KpPeriod,
+        hyperspace: Keycode::Kp5,
+    },
+];
+
+/// A player at `position` shaped like `hull` with `lives` lives, flown with `controls`,
+/// or by the computer if `None`.
+fn new_player(
+    position: Vector,
+    hull: Verts,
+    lives: u32,
+    controls: Option<&ControlScheme>,
+) -> Entity {
+    Entity::builder(EntityKind::Player {
+        accelerate: controls.map(|controls| controls.accelerate),
+        turn_right: controls.map(|controls| controls.turn_right),
+        turn_left: controls.map(|controls| controls.turn_left),
+        fire: controls.map(|controls| controls.fire),
+        charge: controls.map(|controls| controls.charge),
+        charge_frames: None,
+        cycle_weapon: controls.map(|controls| controls.cycle_weapon),
+        weapon: Weapon::Standard,
+        lives,
+        spawn_position: position,
+        invulnerable: 0,
+        stunned: 0,
+        cooldown: 0,
+        idle_frames: 0,
+        firing: false,
+        energy: MAX_ENERGY,
+        cpu: controls.is_none(),
+        bomb: controls.map(|controls| controls.bomb),
+        bombs: STARTING_BOMBS,
+        detonating: false,
+        magnet: controls.map(|controls| controls.magnet),
+        magnet_active: false,
+        hyperspace: controls.map(|controls| controls.hyperspace),
+        hyperspace_cooldown: 0,
+        jumping: false,
+        warp: 0,
+    })
+    .body(Body {
+        position,
+        has_drag: true,
+        ..Default::default()
+    })
+    .shape(hull)
+    .build()
+}
+
+/// Where `count` players start, evenly spaced around the center of the screen,
+/// starting from the left.
+fn spawn_positions(count: usize, bounds: Bounds) -> Vec<Vector> {
+    let center = bounds.size() / 2.0;
+    // A lone player starts in the center
+    let radius = if count > 1 {
+        bounds.size().min_element() / 6.0
+    } else {
+        0.0
+    };
+    (0..count)
+        .map(|i| {
+            let angle = consts::PI + i as Float * consts::TAU / count as Float;
+            center + radius * Vector::new(angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+/// The players and the first wave of asteroids, for starting a game.
+fn initial_entities(settings: &Settings, bounds: Bounds, rng: &mut impl Rng) -> Vec<Entity> {
+    let controls = CONTROL_SCHEMES[..settings.players]
+        .iter()
+        .map(Some)
+        .chain(settings.cpu_player.then_some(None));
+    let positions = spawn_positions(settings.players + settings.cpu_player as usize, bounds);
+    // A lone player keeps the theme's colors
+    let colored = positions.len() > 1;
+    let mut entities: Vec<Entity> = controls
+        .zip(positions)
+        .zip(SHIP_HULLS.iter().cycle())
+        .zip(PLAYER_COLORS.iter().cycle())
+        .map(|(((controls, position), hull), &color)| {
+            let mut player = new_player(position, hull.clone(), settings.lives, controls);
+            player.color = colored.then_some(color);
+            player
+        })
+        .collect();
+    // Kept clear of the players, and of each other, unless bursting out together with `--big-bang`
+    let mut avoid: Vec<Vector> = entities.iter().map(|player| player.body.position).collect();
+    // With `--big-bang`, the asteroids burst out from the middle of the screen while the players,
+    // invulnerable, look on
+    if settings.big_bang {
+        for player in &mut entities {
+            if let EntityKind::Player { invulnerable, .. } = &mut player.kind {
+                *invulnerable = settings.frames(BIG_BANG_SECONDS);
+            }
+        }
+    }
+    entities.extend(
+        [
+            (3, Vector { x: -1.0, y: 2.2 }),
+            (2, Vector { x: 1.0, y: 1.2 }),
+            (1, Vector { x: 2.0, y: -1.6 }),
+        ]
+        .map(|(size, velocity)| {
+            let (position, velocity) = if settings.big_bang {
+                (bounds.size() / 2.0, velocity)
+            } else {
+                let position = safe_spawn_position(bounds, &avoid, SAFE_SPAWN_DISTANCE, rng);
+                avoid.push(position);
+                // Head for the middle of the screen at the same speed
+                let heading = (bounds.size() / 2.0 - position).normalize_or_zero();
+                (position, heading * velocity.length())
+            };
+            let mut asteroid = new_asteroid(
+                size,
+                Body {
+                    position,
+                    prev_position: position,
+                    velocity,
+                    rotation: 0.0,
+                    has_drag: false,
+                    accelerating: false,
+                    turning_left: false,
+                    turning_right: false,
+                },
+                &settings.asteroid_shapes,
+                rng,
+            );
+            if !settings.big_bang {
+                // Starting off-screen, like every later wave
+                asteroid.wrap = WrappingBehavior::OnceOnScreen;
+            }
+            toughen(&mut asteroid, &settings.difficulty);
+            protect_spawn(&mut asteroid, settings);
+            asteroid
+        }),
+    );
+    entities
+}
+
+/// Returns how many frames the intro runs at the start of a game, which is none without
+/// `--big-bang`.
+fn intro_frames(settings: &Settings) -> u64 {
+    if settings.big_bang {
+        settings.frames(BIG_BANG_SECONDS)
+    } else {
+        0
+    }
+}
+
+fn demo_entities(settings: &Settings, bounds: Bounds, rng: &mut impl Rng) -> Vec<Entity> {
+    let mut entities = initial_entities(settings, bounds, rng);
+    // Only the first human player is flown by the autopilot
+    let mut humans = 0;
+    entities.retain(|entity| match entity.kind {
+        EntityKind::Player { cpu: false, .. } => {
+            humans += 1;
+            humans == 1
+        }
+        _ => true,
+    });
+    entities
+}
+
+/// Extra things drawn over the game, toggled while playing.
+#[derive(Debug, Clone, Copy, Default)]
+struct Overlays {
+    /// Collision outlines, aiming reticles and frame timing. Toggled with F3
+    debug: bool,
+    /// Velocity vectors. Toggled with F5
+    velocities: bool,
+    /// Where things started touching. Toggled with F6
+    contacts: bool,
+}
+
+/// Everything simulated, as opposed to how it's shown or the menus shown over it.
+struct GameState {
+    entities: Vec<Entity>,
+    wave: usize,
+    score: u64,
+    /// Frames left on the clock with `--time-attack`. The game is over once it reaches zero.
+    time_left: Option<u64>,
+    /// Frames survived with `--survival`
+    survival_elapsed: u64,
+    /// Frames until the next asteroid with `--survival`
+    next_survival_spawn: u64,
+    /// Frames left of the `--big-bang` intro, during which the players can't be controlled
+    intro_left: u64,
+    /// Ids of the pairs of entities that overlapped last tick, smaller id first
+    touching: HashSet<(u64, u64)>,
+    /// Where the pairs of entities that started touching last tick touched
+    contacts: Vec<Vector>,
+    /// Flown by the autopilot, as the attract demo
+    demo: bool,
+}
+
+impl GameState {
+    /// A new game, from the first wave.
+    fn new(settings: &Settings, bounds: Bounds, rng: &mut impl Rng) -> GameState {
+        GameState {
+            entities: initial_entities(settings, bounds, rng),
+            // The initial asteroids are the first wave
+            wave: 1,
+            score: 0,
+            time_left: settings.time_attack.map(|seconds| settings.frames(seconds)),
+            survival_elapsed: 0,
+            next_survival_spawn: 0,
+            intro_left: intro_frames(settings),
+            touching: HashSet::new(),
+            contacts: vec![],
+            demo: false,
+        }
+    }
+
+    /// A new attract demo, which plays in waves with no clock.
+    fn demo(settings: &Settings, bounds: Bounds, rng: &mut impl Rng) -> GameState {
+        GameState {
+            entities: demo_entities(settings, bounds, rng),
+            wave: 1,
+            score: 0,
+            time_left: None,
+            survival_elapsed: 0,
+            next_survival_spawn: 0,
+            intro_left: 0,
+            touching: HashSet::new(),
+            contacts: vec![],
+            demo: true,
+        }
+    }
+
+    /// True once the clock has run out with `--time-attack`, after which nothing moves and the
+    /// final score is shown until the game is restarted.
+    fn is_over(&self) -> bool {
+        self.time_left == Some(0)
+    }
+
+    /// Passes an input event, such as a key press, to every entity.
+    fn handle_event(&mut self, event: &Event, settings: &Settings, log: &mut EventLog) {
+        let new_entities = self
+            .entities
+            .iter_mut()
+            .flat_map(|entity| entity.handle_event(event, settings))
+            .collect::<Vec<_>>();
+        for entity in &new_entities {
+            log.spawn(entity);
+        }
+        self.entities.extend(new_entities);
+    }
+
+    /// Simulates one tick.
+    fn update(
+        &mut self,
+        bounds: Bounds,
+        settings: &Settings,
+        rng: &mut impl Rng,
+        log: &mut EventLog,
+    ) {
+        if self.is_over() {
+            return;
+        }
+        let GameState {
+            entities,
+            wave,
+            score,
+            time_left,
+            survival_elapsed,
+            next_survival_spawn,
+            intro_left,
+            touching,
+            contacts,
+            demo,
+        } = self;
+
+        log.next_frame();
+
+        // Removing entities reorders the rest, so restore creation order for everything
+        // this tick does to them in turn
+        if settings.stable_order {
+            entities.sort_by_key(|entity| entity.id);
+        }
+
+        if let Some(frames @ 1..) = time_left {
+            *frames -= 1;
+            // Running out of time ends the game, as if everyone ran out of lives
+            if *frames == 0 {
+                entities.retain(|entity| {
+                    let player = matches!(entity.kind, EntityKind::Player { .. });
+                    if player {
+                        log.remove(entity, RemoveReason::Expired);
+                    }
+                    !player
+                });
+            }
+        }
+
+        *intro_left = intro_left.saturating_sub(1);
+
+        // Computer-controlled players decide how to fly before moving, once the intro is over
+        if *intro_left == 0 {
+            for index in 0..entities.len() {
+                if let EntityKind::Player { cpu: true, .. } = entities[index].kind {
+                    let controls = autopilot::cpu_input(entities, index, bounds, settings);
+                    let player = &mut entities[index];
+                    player.body.accelerating = controls.accelerating;
+                    player.body.turning_left = controls.turning_left;
+                    player.body.turning_right = controls.turning_right;
+                    if controls.fire {
+                        let new_entities = player.fire(settings);
+                        for entity in &new_entities {
+                            log.spawn(entity);
+                        }
+                        entities.extend(new_entities);
+                    }
+                }
+            }
+        }
+
+        // Held fire keys fire again whenever the weapon is ready
+        if settings.auto_fire {
+            let mut new_entities = vec![];
+            for entity in entities.iter_mut() {
+                if let EntityKind::Player {
+                    firing: true,
+                    cooldown: 0,
+                    ..
+                } = entity.kind
+                {
+                    new_entities.extend(entity.fire(settings));
+                }
+            }
+            for entity in &new_entities {
+                log.spawn(entity);
+            }
+            entities.extend(new_entities);
+        }
+
+        if *demo {
+            // Start the demo over once the autopilot runs out of lives
+            if !entities
+                .iter()
+                .any(|entity| matches!(entity.kind, EntityKind::Player { .. }))
+            {
+                *entities = demo_entities(settings, bounds, rng);
+                *wave = 1;
+                *score = 0;
+            }
+            for index in 0..entities.len() {
+                if let EntityKind::Player { .. } = entities[index].kind {
+                    for event in autopilot::autopilot(entities, index, bounds, settings) {
+                        let new_entities = entities[index].handle_event(&event, settings);
+                        for entity in &new_entities {
+                            log.spawn(entity);
+                        }
+                        entities.extend(new_entities);
+                    }
+                }
+            }
+        }
+        let mut effects = vec![];
+        entities.retain_mut(|entity| match entity.step(bounds, settings) {
+            StepResult::RemoveEntity(reason) => {
+                log.remove(entity, reason);
+                effects.extend(death_effects(entity, reason, rng));
+                false
+            }
+            StepResult::None => true,
+        });
+        for entity in &effects {
+            log.spawn(entity);
+        }
+        entities.extend(effects);
+
+        // Magnets pull powerups toward the players using them
+        let magnets = entities
+            .iter()
+            .filter(|entity| {
+                matches!(
+                    entity.kind,
+                    EntityKind::Player {
+                        magnet_active: true,
+                        ..
+                    }
+                )
+            })
+            .map(|player| (player.body.position, player.body.rotation))
+            .collect::<Vec<_>>();
+        for powerup in entities.iter_mut() {
+            if let EntityKind::Powerup { .. } = powerup.kind {
+                for &(position, rotation) in &magnets {
+                    if let Some(pull) =
+                        magnet_pull(position, rotation, powerup.body.position, bounds)
+                    {
+                        powerup.body.velocity += pull;
+                    }
+                }
+            }
+        }
+
+        // Removes an entity that has already been taken out of `entities`, adding whatever it
+        // leaves behind
+        macro_rules! remove_entity {
+            ($entity:expr, $reason:expr) => {
+                let entity = $entity;
+                let reason = $reason;
+                log.remove(entity, reason);
+                let effects = death_effects(entity, reason, rng);
+                for effect in &effects {
+                    log.spawn(effect);
+                }
+                entities.extend(effects);
+            };
+        }
+
+        macro_rules! split_asteroid {
+            (asteroid = $asteroid:expr, bullet = $bullet:expr) => {
+                let bullet = $bullet;
+                let EntityKind::Bullet { charged, .. } = bullet.kind else {
+                    unreachable!()
+                };
+                // Split across the way the bullet is travelling, which isn't necessarily
+                // the way it is pointing if it was fired from a turning ship
+                let direction = if bullet.body.velocity == Vector::ZERO {
+                    bullet.body.rotation
+                } else {
+                    rotation_towards(bullet.body.velocity)
+                };
+                // Charged bullets vaporize the asteroid, leaving only debris
+                split_asteroid!(
+                    asteroid = $asteroid,
+                    direction = direction,
+                    vaporize = charged
+                );
+            };
+            (asteroid = $asteroid:expr, direction = $direction:expr, vaporize = $vaporize:expr) => {
+                let asteroid = $asteroid;
+                let EntityKind::Asteroid { size, .. } = asteroid.kind else {
+                    unreachable!()
+                };
+                log.split(&asteroid);
+                remove_entity!(&asteroid, RemoveReason::Destroyed);
+                *score += asteroid_points(size);
+                if let Some(frames @ 1..) = time_left {
+                    *frames += settings.frames(TIME_BONUS_SECONDS);
+                }
+                if size > 1 && !$vaporize {
+                    let split_direction = $direction + consts::FRAC_PI_2;
+                    let mut left_asteroid =
+                        new_asteroid(size - 1, asteroid.body, &settings.asteroid_shapes, rng);
+                    let mut right_asteroid =
+                        new_asteroid(size - 1, asteroid.body, &settings.asteroid_shapes, rng);
+                    if settings.conserve_mass {
+                        let children_area =
+                            sprite_area(&left_asteroid) + sprite_area(&right_asteroid);
+                        let scale = (sprite_area(&asteroid) / children_area).sqrt();
+                        left_asteroid.scale *= scale;
+                        right_asteroid.scale *= scale;
+                    }
+                    // The pieces carry on from the parent's rotation, a little out of line
+                    for child in [&mut left_asteroid, &mut right_asteroid] {
+                        let jitter = rng.gen_range(-SPLIT_ROTATION_JITTER..=SPLIT_ROTATION_JITTER);
+                        child.body.rotation =
+                            (child.body.rotation + jitter).rem_euclid(consts::TAU);
+                    }
+                    let rota = rotation_matrix(split_direction);
+                    let left = rota * Vector { x: 0.0, y: 1.0 };
+                    let right = -left;
+                    left_asteroid.body.velocity += left;
+                    left_asteroid.body.position += left;
+                    right_asteroid.body.velocity += right;
+                    right_asteroid.body.position += right;
+                    log.spawn(&left_asteroid);
+                    log.spawn(&right_asteroid);
+                    entities.extend([left_asteroid, right_asteroid]);
+                }
+                for _ in 0..size * 4 - 2 {
+                    let debris_direction = rng.gen_range(0.0..=consts::TAU);
+                    let rota = rotation_matrix(debris_direction);
+                    let velocity_offset = rota * Vector { x: 0.0, y: 4.0 };
+                    let mut body = asteroid.body;
+                    body.velocity += velocity_offset;
+                    let debris = new_debris(body, DEBRIS_TTL, rng);
+                    log.spawn(&debris);
+                    entities.push(debris);
+                }
+                if size == 1 && rng.gen_bool(POWERUP_DROP_CHANCE) {
+                    let effect = if rng.gen_bool(0.5) {
+                        PowerupEffect::ExtraLife
+                    } else {
+                        PowerupEffect::Bomb
+                    };
+                    let powerup =
+                        new_powerup(asteroid.body, effect, settings.frames(POWERUP_SECONDS));
+                    log.spawn(&powerup);
+                    entities.push(powerup);
+                }
+            };
+            (bullet = $bullet:expr, asteroid = $asteroid:expr) => {
+                let bullet = $bullet;
+                let asteroid = $asteroid;
+                split_asteroid!(asteroid = asteroid, bullet = bullet);
+            };
+        }
+
+        macro_rules! damage_boss {
+            (boss = $boss_index:expr, zone = $zone:expr) => {
+                let boss_index = $boss_index;
+                let EntityKind::Boss { zones } = &mut entities[boss_index].kind else {
+                    unreachable!()
+                };
+                zones[$zone] -= 1;
+                if zones.iter().all(|&health| health == 0) {
+                    let boss_body = entities[boss_index].body;
+                    remove_entity!(&entities.swap_remove(boss_index), RemoveReason::Destroyed);
+                    *score += BOSS_POINTS;
+                    for _ in 0..40 {
+                        let debris_direction = rng.gen_range(0.0..=consts::TAU);
+                        let rota = rotation_matrix(debris_direction);
+                        let velocity_offset = rota
+                            * Vector {
+                                x: 0.0,
+                                y: rng.gen_range(2.0..6.0),
+                            };
+                        let mut body = boss_body;
+                        body.velocity += velocity_offset;
+                        let debris = new_debris(body, DEBRIS_TTL, rng);
+                        log.spawn(&debris);
+                        entities.push(debris);
+                    }
+                }
+            };
+        }
+
+        macro_rules! player_dies {
+            ($player_index:expr) => {
+                let player_index = $player_index;
+                let (result, debris) = kill_player(&mut entities[player_index], settings, rng);
+                if let StepResult::RemoveEntity(reason) = result {
+                    remove_entity!(&entities.swap_remove(player_index), reason);
+                }
+                for debris in &debris {
+                    log.spawn(debris);
+                }
+                entities.extend(debris);
+            };
+        }
+
+        // Hyperspace drops ships somewhere random, which isn't necessarily any safer
+        for entity in entities.iter_mut() {
+            if let EntityKind::Player { jumping, warp, .. } = &mut entity.kind {
+                if std::mem::take(jumping) {
+                    let position = bounds.size()
+                        * Vector::new(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0));
+                    entity.body.position = position;
+                    // Without a trail back to where it jumped from
+                    entity.body.prev_position = position;
+                    entity.body.velocity = Vector::ZERO;
+                    *warp = settings.frames(HYPERSPACE_WARP_SECONDS);
+                    entity.scale = 0.0;
+                }
+            }
+        }
+
+        // Bombs destroy every asteroid on screen
+        let mut bomb_detonated = false;
+        for entity in entities.iter_mut() {
+            if let EntityKind::Player { detonating, .. } = &mut entity.kind {
+                bomb_detonated |= std::mem::take(detonating);
+            }
+        }
+        if bomb_detonated {
+            let mut index = 0;
+            while index < entities.len() {
+                let entity = &entities[index];
+                let position = entity.body.position;
+                if matches!(entity.kind, EntityKind::Asteroid { .. })
+                    && bounds.contains_aabb(position, position)
+                {
+                    let asteroid = entities.swap_remove(index);
+                    split_asteroid!(asteroid = asteroid, direction = 0.0, vaporize = true);
+                } else {
+                    index += 1;
+                }
+            }
+        }
+
+        // Shockwaves destroy the asteroids they have expanded over
+        let shockwaves = entities
+            .iter()
+            .filter_map(|entity| match entity.kind {
+                EntityKind::Shockwave { radius, .. } => Some((entity.body.position, radius)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (center, radius) in shockwaves {
+            let mut index = 0;
+            while index < entities.len() {
+                let entity = &entities[index];
+                if matches!(entity.kind, EntityKind::Asteroid { .. })
+                    && toroidal_delta(center, entity.body.position, bounds).length() <= radius
+                {
+                    let asteroid = entities.swap_remove(index);
+                    split_asteroid!(asteroid = asteroid, direction = 0.0, vaporize = true);
+                } else {
+                    index += 1;
+                }
+            }
+        }
+
+        // Held lasers hit the first asteroid or boss along their beam, once per cooldown
+        let mut lasers = vec![];
+        for entity in entities.iter_mut() {
+            if let EntityKind::Player {
+                weapon: Weapon::Laser,
+                firing: true,
+                cooldown: cooldown @ 0,
+                energy,
+                ..
+            } = &mut entity.kind
+            {
+                if spend_energy(energy, Weapon::Laser.energy_cost()) {
+                    *cooldown = settings.frames(LASER_COOLDOWN_SECONDS);
+                    lasers.push((entity.body, entity.id, entity.color));
+                }
+            }
+        }
+        for (shooter, owner, color) in lasers {
+            let (_, _, Some((index, triangle))) = laser_beam(&shooter, entities, bounds) else {
+                continue;
+            };
+            match &mut entities[index].kind {
+                EntityKind::Boss { .. } => {
+                    damage_boss!(boss = index, zone = triangle / 2);
+                    continue;
+                }
+                // Tough asteroids flash instead of splitting until their last hit, as with bullets
+                EntityKind::Asteroid {
+                    hits_remaining: hits_remaining @ 2..,
+                    flash,
+                    ..
+                } => {
+                    *hits_remaining -= 1;
+                    *flash = settings.frames(HIT_FLASH_SECONDS);
+                    continue;
+                }
+                _ => {}
+            }
+            let asteroid = entities.swap_remove(index);
+            // Split as if by a bullet travelling along the beam
+            let bullet = fire_bullet(&shooter, owner, color, false, 0, settings);
+            split_asteroid!(asteroid = asteroid, bullet = bullet);
+        }
+
+        // Find every collision before resolving any, so that resolving one can't disturb
+        // finding the rest
+        let mut events = vec![];
+        let mut now_touching = HashSet::new();
+        contacts.clear();
+        for (i, j) in (0..entities.len()).tuple_combinations() {
+            let (a, b) = (&entities[i], &entities[j]);
+            if !a.collides_with(b, settings) {
+                continue;
+            }
+            if let Some(contact) = a.collision(b, bounds) {
+                let pair = (a.id.min(b.id), a.id.max(b.id));
+                // Pairs that stay overlapping only collide when they first touch
+                if touching.contains(&pair) {
+                    now_touching.insert(pair);
+                    continue;
+                }
+                contacts.push(contact.point);
+                log.collision(a, b);
+                if let Some(event) = collision_event(a, b, settings) {
+                    let distance =
+                        toroidal_delta(a.body.position, b.body.position, bounds).length();
+                    events.push((distance, pair, event));
+                } else {
+                    now_touching.insert(pair);
+                }
+            }
+        }
+        *touching = now_touching;
+        // Resolve the closest pairs first, rather than in whatever order the entities happen
+        // to be in, so when two bullets hit one asteroid the nearer one splits it and the
+        // other flies on. Ties go to the older entities.
+        events.sort_by(|(a_distance, a_pair, _), (b_distance, b_pair, _)| {
+            a_distance.total_cmp(b_distance).then(a_pair.cmp(b_pair))
+        });
+
+        // Entities are looked up by id each time, since resolving an event can remove and
+        // reorder entities. Events involving an entity that is already gone are skipped.
+        // A pair only counts as touching once its event has taken effect, so e.g. a bullet
+        // passing through a boss's destroyed zone can still hit an intact one.
+        for (_, pair, event) in events {
+            match event {
+                CollisionEvent::AsteroidHitByBullet {
+                    asteroid_id,
+                    bullet_id,
+                } => {
+                    let (Some(asteroid_index), Some(bullet_index)) = (
+                        index_of(entities, asteroid_id),
+                        index_of(entities, bullet_id),
+                    ) else {
+                        continue;
+                    };
+                    let EntityKind::Bullet {
+                        pierces,
+                        intangible,
+                        charged,
+                        ..
+                    } = entities[bullet_index].kind
+                    else {
+                        unreachable!()
+                    };
+                    // Already pierced another asteroid this tick
+                    if intangible > 0 {
+                        continue;
+                    }
+                    // Tough asteroids flash instead of splitting until their last hit, except
+                    // that charged bullets vaporize them outright
+                    if let EntityKind::Asteroid {
+                        hits_remaining: hits_remaining @ 2..,
+                        flash,
+                        ..
+                    } = &mut entities[asteroid_index].kind
+                    {
+                        if !charged {
+                            *hits_remaining -= 1;
+                            *flash = settings.frames(HIT_FLASH_SECONDS);
+                            if pierces > 0 {
+                                pierce(&mut entities[bullet_index]);
+                            } else {
+                                remove_entity!(
+                                    &entities.swap_remove(bullet_index),
+                                    RemoveReason::Destroyed
+                                );
+                            }
+                            touching.insert(pair);
+                            continue;
+                        }
+                    }
+                    if pierces > 0 {
+                        let bullet = pierce(&mut entities[bullet_index]);
+                        let asteroid = entities.swap_remove(asteroid_index);
+                        split_asteroid!(asteroid = asteroid, bullet = bullet);
+                    } else {
+                        let (asteroid, bullet) =
+                            remove_pair(entities, asteroid_index, bullet_index);
+                        remove_entity!(&bullet, RemoveReason::Destroyed);
+                        split_asteroid!(asteroid = asteroid, bullet = bullet);
+                    }
+                }
+                CollisionEvent::BossHitByBullet { boss_id, bullet_id } => {
+                    let (Some(boss_index), Some(bullet_index)) =
+                        (index_of(entities, boss_id), index_of(entities, bullet_id))
+                    else {
+                        continue;
+                    };
+                    let bullet_position = entities[bullet_index].body.position;
+                    // Bullets pass through destroyed zones, and may yet hit an intact one
+                    // further in
+                    let Some(zone) = boss_zone_at(&entities[boss_index], bullet_position, bounds)
+                    else {
+                        continue;
+                    };
+                    remove_entity!(&entities.swap_remove(bullet_index), RemoveReason::Destroyed);
+                    let boss_index = index_of(entities, boss_id).unwrap();
+                    damage_boss!(boss = boss_index, zone = zone);
+                }
+                CollisionEvent::PlayerBumped {
+                    player_id,
+                    asteroid_id,
+                } => {
+                    let (Some(player_index), Some(asteroid_index)) = (
+                        index_of(entities, player_id),
+                        index_of(entities, asteroid_id),
+                    ) else {
+                        continue;
+                    };
+                    let asteroid_position = entities[asteroid_index].body.position;
+                    bump_player(
+                        &mut entities[player_index],
+                        asteroid_position,
+                        bounds,
+                        settings,
+                    );
+                }
+                CollisionEvent::DebrisBounced {
+                    debris_id,
+                    asteroid_id,
+                } => {
+                    let (Some(debris_index), Some(asteroid_index)) = (
+                        index_of(entities, debris_id),
+                        index_of(entities, asteroid_id),
+                    ) else {
+                        continue;
+                    };
+                    let asteroid_position = entities[asteroid_index].body.position;
+                    bounce_debris(&mut entities[debris_index], asteroid_position, bounds);
+                }
+                CollisionEvent::PlayerDied { player_id } => {
+                    let Some(player_index) = index_of(entities, player_id) else {
+                        continue;
+                    };
+                    // Already died and respawned this tick
+                    if let EntityKind::Player {
+                        invulnerable: 1.., ..
+                    } = entities[player_index].kind
+                    {
+                        continue;
+                    }
+                    player_dies!(player_index);
+                }
+                CollisionEvent::PlayerShot {
+                    player_id,
+                    bullet_id,
+                } => {
+                    let (Some(player_index), Some(bullet_index)) =
+                        (index_of(entities, player_id), index_of(entities, bullet_id))
+                    else {
+                        continue;
+                    };
+                    if let EntityKind::Player {
+                        invulnerable: 1.., ..
+                    } = entities[player_index].kind
+                    {
+                        continue;
+                    }
+                    if let EntityKind::Bullet { owner, .. } = entities[bullet_index].kind {
+                        if owner == player_id {
+                            continue;
+                        }
+                    }
+                    remove_entity!(&entities.swap_remove(bullet_index), RemoveReason::Destroyed);
+                    let player_index = index_of(entities, player_id).unwrap();
+                    player_dies!(player_index);
+                }
+                CollisionEvent::PowerupCollected {
+                    player_id,
+                    powerup_id,
+                } => {
+                    let (Some(player_index), Some(powerup_index)) = (
+                        index_of(entities, player_id),
+                        index_of(entities, powerup_id),
+                    ) else {
+                        continue;
+                    };
+                    let EntityKind::Powerup { effect, .. } = entities[powerup_index].kind else {
+                        unreachable!()
+                    };
+                    let new_entities = collect_powerup(&mut entities[player_index], effect);
+                    log.remove(
+                        &entities.swap_remove(powerup_index),
+                        RemoveReason::Collected,
+                    );
+                    for entity in &new_entities {
+                        log.spawn(entity);
+                    }
+                    entities.extend(new_entities);
+                }
+            }
+            touching.insert(pair);
+        }
+
+        // Survival asteroids arrive one at a time, ever faster, instead of in waves.
+        // The attract demo always plays in waves.
+        if settings.survival && !*demo {
+            *survival_elapsed += 1;
+            let elapsed = *survival_elapsed as Float / settings.fps as Float;
+            *wave = 1 + (elapsed / SURVIVAL_SECONDS_PER_WAVE) as usize;
+            *next_survival_spawn = next_survival_spawn.saturating_sub(1);
+            if *next_survival_spawn == 0 && asteroids_remaining(entities) < SURVIVAL_MAX_ASTEROIDS {
+                let players: Vec<Vector> = entities
+                    .iter()
+                    .filter(|entity| matches!(entity.kind, EntityKind::Player { .. }))
+                    .map(|entity| entity.body.position)
+                    .collect();
+                let size = rng.gen_range(1..=settings.difficulty.max_asteroid_size(*wave));
+                let speed_multiplier = settings.difficulty.asteroid_speed_multiplier(*wave);
+                let mut asteroid = spawn_asteroid(
+                    size,
+                    speed_multiplier,
+                    &settings.asteroid_shapes,
+                    bounds,
+                    &players,
+                    rng,
+                );
+                toughen(&mut asteroid, &settings.difficulty);
+                protect_spawn(&mut asteroid, settings);
+                log.spawn(&asteroid);
+                entities.push(asteroid);
+                *next_survival_spawn = settings.frames(survival_spawn_interval(elapsed));
+            }
+        } else if asteroids_remaining(entities) == 0 {
+            *wave += 1;
+            let players: Vec<Vector> = entities
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Player { .. }))
+                .map(|entity| entity.body.position)
+                .collect();
+            let mut new_wave = spawn_wave(
+                *wave,
+                &settings.difficulty,
+                &settings.asteroid_shapes,
+                bounds,
+                &players,
+                rng,
+            );
+            for asteroid in &mut new_wave {
+                protect_spawn(asteroid, settings);
+                log.spawn(asteroid);
+            }
+            entities.extend(new_wave);
+        }
+    }
+
+    /// Draws the game, without changing it, as of the last tick.
+    ///
+    /// Everything in the play area is drawn, mirrored with `--invert-y` and wrapped around the
+    /// edges, along with the clock and demo banner above it.
+    fn render<T: RenderTarget>(
+        &self,
+        lines: &mut LineRenderer,
+        canvas: &mut Canvas<T>,
+        bounds: Bounds,
+        settings: &Settings,
+        theme: &Theme,
+        overlays: Overlays,
+    ) {
+        lines.set_mirror(settings.invert_y.then(|| bounds.size().y));
+        // A faint grid behind everything, for judging motion and where things wrap
+        if overlays.debug {
+            let color = shade(theme.hud, 0.15);
+            for (start, end) in bounds.grid_lines(settings.grid_spacing) {
+                lines.line(canvas, start, end, color);
+            }
+        }
+        // entities.sort_unstable_by_key(|entity| float_ord::FloatOrd(entity.body.position.y));
+        for entity in &self.entities {
+            let pos = entity.body.position;
+            let rota = rotation_matrix(entity.body.rotation) * entity.scale;
+
+            // canvas.set_draw_color(hue_to_color((hue + entity.color_offset) % (255 * 6)));
+            let mut color = entity.color.unwrap_or_else(|| theme.color_of(&entity.kind));
+
+            // A skin's colors replace the theme's, though asteroids still flash when hit
+            if let EntityKind::Asteroid { size, flash: 0, .. } = entity.kind {
+                if let Some(skin_color) = settings.asteroid_colors[size - 1] {
+                    color = skin_color;
+                }
+            }
+
+            // Respawned players pulse in and out of view while they are invulnerable, and newly
+            // spawned asteroids while they can't collide yet
+            let protected_frames = match entity.kind {
+                EntityKind::Player { invulnerable, .. } => invulnerable,
+                EntityKind::Asteroid {
+                    spawn_protection, ..
+                } => spawn_protection,
+                _ => 0,
+            };
+            if protected_frames > 0 {
+                let pulse = (protected_frames as Float * consts::TAU / 16.0).cos() * 0.5 + 0.5;
+                let transparent = Color { a: 0, ..color };
+                color = lerp_color(transparent, color, 0.2 + 0.8 * pulse);
+            }
+
+            if let Some(verts) = &entity.sprite_verts {
+                // The whole sprite is drawn at each copy, rather than deciding which copies each
+                // line needs separately, so a sprite on the seam is never partly drawn
+                let copies: Vec<Vector> = if matches!(entity.wrap, WrappingBehavior::Yes) {
+                    let reach = verts
+                        .verts
+                        .iter()
+                        .map(|&vert| (rota * vert).length())
+                        .fold(0.0, Float::max);
+                    bounds.wrapped_copies(pos, reach).collect()
+                } else {
+                    vec![pos]
+                };
+                for (p1, p2) in verts.verts.iter().copied().circular_tuple_windows() {
+                    for &copy in &copies {
+                        lines.line(canvas, rota * p1 + copy, rota * p2 + copy, color);
+                    }
+                }
+                if settings.craters {
+                    let detail = shade(color, 0.6);
+                    for &(p1, p2) in &entity.sprite_details {
+                        for &copy in &copies {
+                            lines.line(canvas, rota * p1 + copy, rota * p2 + copy, detail);
+                        }
+                    }
+                }
+            }
+
+            if let EntityKind::Bullet { .. } = entity.kind {
+                let (start, end) = trail_segment(&entity.body, bounds);
+                for offset in bounds.wrap_offsets(start.min(end), start.max(end)) {
+                    lines.line(canvas, start + offset, end + offset, shade(color, 0.4));
+                }
+            }
+
+            if let EntityKind::Player {
+                weapon: Weapon::Laser,
+                firing: true,
+                ..
+            } = entity.kind
+            {
+                let (start, end, _) = laser_beam(&entity.body, &self.entities, bounds);
+                for offset in bounds.wrap_offsets(start.min(end), start.max(end)) {
+                    lines.line(canvas, start + offset, end + offset, theme.bullet);
+                }
+            }
+
+            // Divide bosses into their zones
+            if let EntityKind::Boss { .. } = entity.kind {
+                for vert in verts_of_boss_zones() {
+                    lines.line(canvas, pos, rota * vert + pos, shade(color, 0.5));
+                }
+            }
+
+            // Draw an energy meter under players who have used some
+            if let EntityKind::Player { energy, .. } = entity.kind {
+                if energy < MAX_ENERGY {
+                    let start = pos + Vector { x: -15.0, y: 25.0 };
+                    let end = start
+                        + Vector {
+                            x: 30.0 * energy / MAX_ENERGY,
+                            y: 0.0,
+                        };
+                    lines.line(canvas, start, end, theme.hud);
+                }
+            }
+
+            // Draw a tick under players for each bomb they have left
+            if let EntityKind::Player { bombs, .. } = entity.kind {
+                for i in 0..bombs {
+                    let top = pos
+                        + Vector {
+                            x: -15.0 + 4.0 * i as Float,
+                            y: 29.0,
+                        };
+                    lines.line(canvas, top, top + Vector::Y * 3.0, theme.hud);
+                }
+            }
+
+            // Draw a small copy of the ship under players for each spare life
+            if let (EntityKind::Player { lives, .. }, Some(verts)) =
+                (entity.kind, &entity.sprite_verts)
+            {
+                for i in 1..lives {
+                    let center = pos
+                        + Vector {
+                            x: -12.0 + 8.0 * (i - 1) as Float,
+                            y: 40.0,
+                        };
+                    for (p1, p2) in verts.verts.iter().copied().circular_tuple_windows() {
+                        lines.line(canvas, center + p1 * 0.25, center + p2 * 0.25, theme.hud);
+                    }
+                }
+            }
+
+            // Draw a faint cone where an active magnet reaches
+            if let EntityKind::Player {
+                magnet_active: true,
+                ..
+            } = entity.kind
+            {
+                let color = shade(color, 0.25);
+                for angle in [-MAGNET_HALF_ANGLE, MAGNET_HALF_ANGLE] {
+                    let edge = forward(entity.body.rotation + angle) * MAGNET_RADIUS;
+                    lines.line(canvas, pos, pos + edge, color);
+                }
+            }
+
+            // canvas
+            //     .fill_rect(Rect::new(x as i32 - 40, y as i32 - 40, 80, 80))
+            //     .ok();
+        }
+
+        // Edges that don't wrap are walls
+        let size = bounds.size();
+        let corners = [
+            Vector::ZERO,
+            Vector::new(size.x - 1.0, 0.0),
+            size - 1.0,
+            Vector::new(0.0, size.y - 1.0),
+        ];
+        // Top, right, bottom and left, each of which wraps if the axis across it does
+        for (i, axis) in [1, 0, 1, 0].into_iter().enumerate() {
+            if !bounds.wraps(axis) {
+                lines.line(canvas, corners[i], corners[(i + 1) % 4], theme.hud);
+            }
+        }
+
+        // Point arrows at things still approaching from off-screen, at the edge they will enter
+        for entity in &self.entities {
+            let position = entity.body.position;
+            if !matches!(entity.wrap, WrappingBehavior::OnceOnScreen)
+                || bounds.contains_aabb(position, position)
+            {
+                continue;
+            }
+            let tip = bounds.border_point(position, EDGE_INDICATOR_MARGIN);
+            let direction = (position - tip).normalize_or_zero();
+            let back = tip - direction * 12.0;
+            let side = direction.perp() * 6.0;
+            let color = theme.color_of(&entity.kind);
+            lines.line(canvas, tip, back + side, color);
+            lines.line(canvas, back + side, back - side, color);
+            lines.line(canvas, back - side, tip, color);
+        }
+
+        if overlays.debug {
+            // Draw a reticle where each player would need to aim to hit the nearest other player
+            let color = shade(theme.hud, 0.3);
+            let players = self
+                .entities
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Player { .. }));
+            for shooter in players.clone() {
+                let wrap = matches!(shooter.wrap, WrappingBehavior::Yes);
+                let others = players
+                    .clone()
+                    .filter(|&other| !std::ptr::eq(shooter, other));
+                let Some(target) = nearest(shooter.body.position, others, bounds, wrap) else {
+                    continue;
+                };
+                let target_position = if wrap {
+                    shooter.body.position
+                        + toroidal_delta(shooter.body.position, target.body.position, bounds)
+                } else {
+                    target.body.position
+                };
+                let Some(aim) = lead_target(
+                    shooter.body.position,
+                    target_position,
+                    target.body.velocity - shooter.body.velocity,
+                    settings.physics.muzzle_speed,
+                ) else {
+                    continue;
+                };
+                for direction in [Vector::X, Vector::Y, -Vector::X, -Vector::Y] {
+                    lines.line(canvas, aim + direction * 2.0, aim + direction * 6.0, color);
+                }
+            }
+
+            // Outline where wrapping entities reach across the edges, to check collisions there
+            let phantom_color = Color::MAGENTA;
+            for entity in &self.entities {
+                for [a, b, c] in entity.phantom_triangles(bounds) {
+                    lines.line(canvas, a, b, phantom_color);
+                    lines.line(canvas, b, c, phantom_color);
+                    lines.line(canvas, c, a, phantom_color);
+                }
+            }
+        }
+
+        if overlays.velocities {
+            let color = shade(theme.hud, 0.6);
+            for entity in &self.entities {
+                let (start, end) = velocity_vector(&entity.body);
+                // Wrapped like sprites, so lines crossing the seam continue on the other side
+                let copies: Vec<Vector> = if matches!(entity.wrap, WrappingBehavior::Yes) {
+                    bounds
+                        .wrapped_copies(start, (end - start).length())
+                        .collect()
+                } else {
+                    vec![start]
+                };
+                for copy in copies {
+                    let offset = copy - start;
+                    lines.line(canvas, start + offset, end + offset, color);
+                }
+            }
+        }
+
+        if overlays.contacts {
+            for &contact in &self.contacts {
+                for offset in [Vector::new(3.0, 3.0), Vector::new(3.0, -3.0)] {
+                    lines.line(canvas, contact - offset, contact + offset, Color::YELLOW);
+                }
+            }
+        }
+
+        // Text reads the same way up either way
+        lines.set_mirror(None);
+
+        if let Some(frames) = self.time_left {
+            let text = match frames {
+                0 => format!("Time up - Score: {}", self.score),
+                _ => format!("Time: {}", frames.div_ceil(settings.fps.into())),
+            };
+            let position =
+                Vector::new((bounds.size().x - text::text_width(&text, 3.0)) / 2.0, 20.0);
+            text::draw_text(lines, canvas, &text, position, 3.0, theme.hud);
+        }
+
+        if self.is_over() {
+            let text = "Press Enter to play again";
+            let position = Vector::new(
+                (bounds.size().x - text::text_width(text, 2.0)) / 2.0,
+                bounds.size().y / 2.0,
+            );
+            text::draw_text(lines, canvas, text, position, 2.0, theme.hud);
+        }
+
+        if self.demo {
+            let text = "Demo - press any key";
+            let position = Vector::new((bounds.size().x - text::text_width(text, 3.0)) / 2.0, 20.0);
+            text::draw_text(lines, canvas, text, position, 3.0, theme.hud);
+        }
+    }
+}
+
+pub fn main() {
+    let settings = match Settings::from_args(std::env::args().skip(1)) {
+        Ok(settings) => settings,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(message) = run(settings) {
+        eprintln!("Error: {message}");
+        std::process::exit(1);
+    }
+}
+
+/// Describes SDL's video failing to start, which is usually because there is no display.
+fn video_init_error(sdl_error: String) -> String {
+    format!("Failed to initialize video (is a display available?): {sdl_error}")
+}
+
+/// The window title, which shows the score and wave until there is a HUD.
+fn window_title(title: &str, score: u64, wave: usize) -> String {
+    format!("{title} - Score: {score} - Wave: {wave}")
+}
+
+/// Width and height of the window icon, in pixels
+const ICON_SIZE: u32 = 32;
+
+/// Draws the window icon: the first player's ship in outline, white on transparent.
+fn window_icon() -> Result<Surface<'static>, String> {
+    let mut icon = Surface::new(ICON_SIZE, ICON_SIZE, PixelFormatEnum::RGBA32)?;
+    // The hull is 30 pixels tall, with its center a third of the way up from the bottom
+    let offset = Vector::new(ICON_SIZE as Float / 2.0, ICON_SIZE as Float * 2.0 / 3.0);
+    let dots: Vec<Rect> = SHIP_HULLS[0]
+        .iter()
+        .circular_tuple_windows()
+        .flat_map(|(&start, &end)| {
+            (0..ICON_SIZE).map(move |step| {
+                let point = start.lerp(end, step as Float / ICON_SIZE as Float) + offset;
+                Rect::new(point.x as i32 - 1, point.y as i32 - 1, 2, 2)
+            })
+        })
+        .collect();
+    icon.fill_rects(&dots, Color::WHITE)?;
+    Ok(icon)
+}
+
+/// Pauses the game when the window loses focus, with `auto_paused` set, and resumes it when the
+/// window regains focus if it was paused that way.
+fn pause_while_unfocused(event: &Event, paused: &mut Option<PauseMenu>, auto_paused: &mut bool) {
+    match event {
+        Event::Window {
+            win_event: WindowEvent::FocusLost,
+            ..
+        } if paused.is_none() => {
+            *paused = Some(PauseMenu::default());
+            *auto_paused = true;
+        }
+        Event::Window {
+            win_event: WindowEvent::FocusGained,
+            ..
+        } if std::mem::take(auto_paused) => *paused = None,
+        _ => {}
+    }
+}
+
+/// Converts a click at `click` in a window `window_size` across to where it is in the play area.
+///
+/// Clicks are in window coordinates, which may be scaled from the renderer's `output` on high-DPI
+/// displays, and are upside down with `--invert-y`.
+fn click_to_world(
+    click: (i32, i32),
+    window_size: (u32, u32),
+    output: (u32, u32),
+    low_res: Option<&LowRes>,
+    scale: Float,
+    bounds: Bounds,
+    invert_y: bool,
+) -> Vector {
+    let output_scale = Vector::new(
+        output.0 as Float / window_size.0 as Float,
+        output.1 as Float / window_size.1 as Float,
+    );
+    let position = Vector::new(click.0 as Float, click.1 as Float) * output_scale;
+    let mut position = match low_res {
+        Some(low_res) => low_res.to_internal(position, output),
+        None => position / scale,
+    };
+    if invert_y {
+        position.y = bounds.size().y - position.y;
+    }
+    position
+}
+
+/// A still asteroid of `size` at `position`, as clicking spawns with `--debug`.
+fn debug_asteroid(
+    size: usize,
+    position: Vector,
+    settings: &Settings,
+    rng: &mut impl Rng,
+) -> Entity {
+    let body = Body {
+        position,
+        prev_position: position,
+        rotation: rng.gen_range(0.0..consts::TAU),
+        ..Default::default()
+    };
+    new_asteroid(size, body, &settings.asteroid_shapes, rng)
+}
+
+/// Returns the size in pixels of what the game is drawn into, and how many of those pixels each
+/// unit of the play area is drawn across.
+///
+/// Without `low_res`, that is the window, at the display's own scale so high-DPI displays show the
+/// same play area in finer detail. `--render-scale` multiplies the scale either way.
+fn frame_layout(
+    canvas: &WindowCanvas,
+    low_res: Option<&LowRes>,
+    settings: &Settings,
+) -> Result<((u32, u32), Float), String> {
+    if let Some(low_res) = low_res {
+        return Ok((low_res.texture_size(), settings.render_scale));
+    }
+    let output = canvas.output_size()?;
+    let (width, _) = canvas.window().size();
+    // A minimized window may have no size to compare
+    let display_scale = if output.0 > 0 && width > 0 {
+        output.0 as Float / width as Float
+    } else {
+        1.0
+    };
+    Ok((output, display_scale * settings.render_scale))
+}
+
+/// The play area of a frame `frame_size` pixels across, drawn at `scale` pixels per unit.
+fn play_area(frame_size: (u32, u32), scale: Float, wrap: WrapAxes) -> Bounds {
+    let size = Vector::new(frame_size.0 as Float, frame_size.1 as Float) / scale;
+    Bounds::new(
+        UVec2::new(size.x.round() as u32, size.y.round() as u32),
+        wrap,
+    )
+}
+
+fn run(mut settings: Settings) -> Result<(), String> {
+    let sdl_context = sdl2::init().map_err(|e| format!("Failed to initialize SDL: {e}"))?;
+    let video_subsystem = sdl_context.video().map_err(video_init_error)?;
+
+    let mut window = video_subsystem
+        .window(&window_title(&settings.title, 0, 1), 800, 600)
+        .position_centered()
+        .allow_highdpi()
+        .resizable()
+        .build()
+        .map_err(|e| format!("Failed to create window: {e}"))?;
+
+    window
+        .set_minimum_size(100, 100)
+        .map_err(|e| format!("Failed to set minimum window size: {e}"))?;
+    // Only cosmetic, so the game carries on with the default icon without it
+    match window_icon() {
+        Ok(icon) => window.set_icon(icon),
+        Err(e) => eprintln!("Failed to create window icon: {e}"),
+    }
+
+    let mut canvas = settings
+        .present_mode
+        .configure(window.into_canvas())
+        .build()
+        .map_err(|e| format!("Failed to create renderer: {e}"))?;
+
+    canvas.set_draw_color(settings.theme.background);
+    canvas.clear();
+    canvas.present();
+
+    let mut lines = LineRenderer::new(&mut canvas, &settings);
+    let texture_creator = canvas.texture_creator();
+    // `None` if not drawing trails
+    let mut trails =
+        (settings.trail_fade > 0.0).then(|| Trails::new(&texture_creator, settings.trail_fade));
+    // `None` if drawing at the window's own resolution
+    let low_res = match settings.resolution {
+        Some(size) => Some(
+            LowRes::new(&texture_creator, size, settings.render_scale)
+                .map_err(|e| format!("Failed to create low resolution frame: {e}"))?,
+        ),
+        None => None,
+    };
+
+    let mut event_pump = sdl_context
+        .event_pump()
+        .map_err(|e| format!("Failed to get SDL event pump: {e}"))?;
+    let tick_length = Duration::new(1, 0) / settings.fps;
+    let mut pacer = Pacer::new(tick_length)?;
+    // Only used when the frame rate isn't paced
+    let mut last_frame = Instant::now();
+    let mut unsimulated = Duration::ZERO;
+    // How far behind the last frame fell, shown in the debug overlay
+    let mut overrun = Duration::ZERO;
+    let mut rng = match settings.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    // Updated at the start of each frame, in case the window was resized
+    let (frame_size, scale) = frame_layout(&canvas, low_res.as_ref(), &settings)
+        .map_err(|e| format!("Failed to get window size: {e}"))?;
+    let mut bounds = play_area(frame_size, scale, settings.wrap);
+    let mut game = GameState::new(&settings, bounds, &mut rng);
+
+    let mut log = match &settings.debug_log {
+        Some(path) => {
+            EventLog::open(path).map_err(|e| format!("Failed to open debug log `{path}`: {e}"))?
+        }
+        None => EventLog::disabled(),
+    };
+
+    let mut overlays = Overlays::default();
+    // Cycled with F4
+    let mut theme = settings.theme;
+    // Toggled with P. The simulation is frozen while this is `Some`.
+    let mut paused: Option<PauseMenu> = None;
+    // Whether `paused` was set by the window losing focus with `--pause-unfocused`, so it is
+    // cleared again when the window regains focus
+    let mut auto_paused = false;
+    // The size of asteroid clicking spawns with `--debug`, changed with the scroll wheel
+    let mut debug_spawn_size = MAX_ASTEROID_SIZE;
+    // Toggled with the backtick key. Typing goes to the console while this is `Some`.
+    let mut console: Option<Console> = None;
+    // When a key was last pressed, for starting the attract demo
+    let mut last_key_press = Instant::now();
+    // While the attract demo runs, the paused game
+    let mut demo: Option<GameState> = None;
+    // Only set when it changes, rather than every frame
+    let mut title = window_title(&settings.title, game.score, game.wave);
+
+    let result = 'running: loop {
+        if paused.is_some() && last_key_press.elapsed() >= Duration::from_secs(DEMO_IDLE_SECONDS) {
+            let demo_game = GameState::demo(&settings, bounds, &mut rng);
+            demo = Some(std::mem::replace(&mut game, demo_game));
+            paused = None;
+            auto_paused = false;
+        }
+
+        for event in event_pump.poll_iter() {
+            if let Event::KeyDown { .. } = event {
+                last_key_press = Instant::now();
+                // Any key ends the demo, back to the pause menu
+                if let Some(paused_game) = demo.take() {
+                    game = paused_game;
+                    paused = Some(PauseMenu::default());
+                    continue;
+                }
+            }
+            // While paused, typing or watching the intro, only key releases reach the entities,
+            // so keys released meanwhile aren't still held afterwards
+            let playing = paused.is_none() && console.is_none() && game.intro_left == 0;
+            if playing || matches!(event, Event::KeyUp { .. }) {
+                game.handle_event(&event, &settings, &mut log);
+            }
+            if settings.pause_unfocused && demo.is_none() {
+                pause_while_unfocused(&event, &mut paused, &mut auto_paused);
+            }
+            if let Some(typing) = &mut console {
+                match event {
+                    Event::Quit { .. } => break 'running Ok(()),
+                    Event::TextInput { text, .. } => typing.type_text(&text),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Backquote | Keycode::Escape),
+                        ..
+                    } => {
+                        console = None;
+                        video_subsystem.text_input().stop();
+                    }
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Backspace),
+                        ..
+                    } => typing.backspace(),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Return | Keycode::KpEnter),
+                        ..
+                    } => typing.submit(&mut settings.physics),
+                    _ => {}
+                }
+                continue;
+            }
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running Ok(()),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backquote),
+                    repeat: false,
+                    ..
+                } => {
+                    console = Some(Console::default());
+                    video_subsystem.text_input().start();
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F3),
+                    repeat: false,
+                    ..
+                } => overlays.debug = !overlays.debug,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F4),
+                    repeat: false,
+                    ..
+                } => theme = theme.next(),
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    repeat: false,
+                    ..
+                } => overlays.velocities = !overlays.velocities,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F6),
+                    repeat: false,
+                    ..
+                } => overlays.contacts = !overlays.contacts,
+                Event::MouseButtonDown {
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } if settings.debug && demo.is_none() => {
+                    let window_size = canvas.window().size();
+                    let output = canvas.output_size().unwrap_or(window_size);
+                    let position = click_to_world(
+                        (x, y),
+                        window_size,
+                        output,
+                        low_res.as_ref(),
+                        lines.scale(),
+                        bounds,
+                        settings.invert_y,
+                    );
+                    let asteroid = debug_asteroid(debug_spawn_size, position, &settings, &mut rng);
+                    log.spawn(&asteroid);
+                    game.entities.push(asteroid);
+                }
+                Event::MouseWheel { y: 1.., .. } if settings.debug => {
+                    debug_spawn_size = debug_spawn_size % MAX_ASTEROID_SIZE + 1;
+                }
+                Event::MouseWheel { y: ..=-1, .. } if settings.debug => {
+                    debug_spawn_size =
+                        (debug_spawn_size + MAX_ASTEROID_SIZE - 2) % MAX_ASTEROID_SIZE + 1;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return | Keycode::KpEnter),
+                    repeat: false,
+                    ..
+                } if game.is_over() && paused.is_none() => {
+                    game = GameState::new(&settings, bounds, &mut rng);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    repeat: false,
+                    ..
+                } => {
+                    paused = match paused {
+                        Some(_) => None,
+                        None => Some(PauseMenu::default()),
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => match paused.as_mut().and_then(|menu| menu.handle_key(keycode)) {
+                    Some(PauseOption::Resume) => paused = None,
+                    Some(PauseOption::Restart) => {
+                        game = GameState::new(&settings, bounds, &mut rng);
+                        paused = None;
+                    }
+                    // Waves already under way keep their difficulty
+                    Some(PauseOption::Difficulty) => {
+                        settings.difficulty = settings.difficulty.next();
+                    }
+                    Some(PauseOption::Quit) => break 'running Ok(()),
+                    None => {}
+                },
+                _ => {}
+            }
+        }
+        // The rest of the game loop goes here...
+
+        let (frame_size, scale) = match frame_layout(&canvas, low_res.as_ref(), &settings) {
+            Ok(layout) => layout,
+            Err(e) => break Err(format!("Failed to get window size: {e}")),
+        };
+        let frame_bounds = play_area(frame_size, scale, settings.wrap);
+        // A minimized window can have no size at all, so the game waits at its last size until
+        // the window is restored, rather than squeezing everything into nothing
+        if frame_bounds.is_empty() {
+            pacer.wait();
+            last_frame = Instant::now();
+            continue;
+        }
+        bounds = frame_bounds;
+        lines.set_scale(scale);
+
+        if let Some(low_res) = &low_res {
+            if let Err(e) = low_res.begin(&mut canvas) {
+                break Err(format!("Failed to draw at low resolution: {e}"));
+            }
+        }
+        if let Some(trails_renderer) = &mut trails {
+            if let Err(e) = trails_renderer.begin(&mut canvas, theme.background, frame_size) {
+                eprintln!("Failed to draw trails, falling back to clearing each frame: {e}");
+                trails = None;
+            }
+        }
+        if trails.is_none() {
+            canvas.set_draw_color(theme.background);
+            canvas.clear();
+        }
+
+        // With the frame rate paced, simulate one tick per frame.
+        // Otherwise, simulate as many fixed-length ticks as have elapsed since the last frame,
+        // up to a limit so a slow machine can't fall further and further behind.
+        let ticks = match settings.present_mode {
+            _ if paused.is_some() => {
+                last_frame = Instant::now();
+                0
+            }
+            PresentMode::Paced => 1,
+            PresentMode::Vsync | PresentMode::Uncapped => {
+                let now = Instant::now();
+                let catch_up = pacing::catch_up(
+                    unsimulated + (now - last_frame),
+                    tick_length,
+                    MAX_CATCH_UP_TICKS,
+                );
+                last_frame = now;
+                unsimulated = catch_up.leftover;
+                overrun = catch_up.dropped;
+                catch_up.ticks
+            }
+        };
+
+        for _ in 0..ticks {
+            game.update(bounds, &settings, &mut rng, &mut log);
+        }
+
+        game.render(&mut lines, &mut canvas, bounds, &settings, &theme, overlays);
+
+        if overlays.debug {
+            let text = format!("Overrun: {} ms", overrun.as_millis());
+            let position = Vector::new(10.0, 10.0);
+            let color = shade(theme.hud, 0.5);
+            text::draw_text(&mut lines, &mut canvas, &text, position, 2.0, color);
+            if settings.debug {
+                let text = format!("Click spawns size {debug_spawn_size}");
+                let position = position + Vector::new(0.0, 2.0 * (text::GLYPH_HEIGHT + 3.0));
+                text::draw_text(&mut lines, &mut canvas, &text, position, 2.0, color);
+            }
+        }
+
+        if let Some(console) = &console {
+            console.draw(&mut lines, &mut canvas, bounds, &theme);
+        }
+
+        // Dim the frozen scene behind the pause menu
+        if let Some(menu) = &paused {
+            lines.flush(&mut canvas);
+            let dim = theme.background;
+            canvas.set_draw_color(Color::RGBA(dim.r, dim.g, dim.b, 160));
+            canvas.fill_rect(None).ok();
+            menu.draw(
+                &mut lines,
+                &mut canvas,
+                bounds,
+                &theme,
+                &settings.difficulty,
+            );
+        }
+
+        lines.flush(&mut canvas);
+
+        if let Some(trails_renderer) = &mut trails {
+            let target = low_res.as_ref().map(LowRes::texture);
+            if let Err(e) = trails_renderer.end(&mut canvas, target) {
+                break Err(format!("Failed to draw trails: {e}"));
+            }
+        }
+        if let Some(low_res) = &low_res {
+            if let Err(e) = low_res.end(&mut canvas, theme.background) {
+                break Err(format!("Failed to draw at low resolution: {e}"));
+            }
+        }
+
+        let new_title = window_title(&settings.title, game.score, game.wave);
+        if new_title != title {
+            if let Err(e) = canvas.window_mut().set_title(&new_title) {
+                break Err(format!("Failed to set window title: {e}"));
+            }
+            title = new_title;
+        }
+
+        canvas.present();
+        if let PresentMode::Paced = settings.present_mode {
+            overrun = pacer.wait();
+        }
+    };
+    // The score of the game, not of the demo interrupting it
+    log.game_over(demo.map_or(game.score, |paused_game| paused_game.score));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use sdl2::pixels::PixelFormatEnum;
+    use sdl2::surface::Surface;
+
+    use super::*;
+    use crate::bounds::WrapAxes;
+    use crate::theme::Theme;
+
+    /// An 800x600 play area.
+    fn bounds() -> Bounds {
+        Bounds::new(UVec2::new(800, 600), WrapAxes::BOTH)
+    }
+
+    fn ship_body(position: Vector, rotation: Float) -> Body {
+        Body {
+            position,
+            prev_position: position,
+            rotation,
+            ..Default::default()
+        }
+    }
+
+    /// A game of just `entities`, without the usual players and first wave.
+    fn game_with(entities: Vec<Entity>) -> GameState {
+        GameState {
+            entities,
+            wave: 1,
+            score: 0,
+            time_left: None,
+            survival_elapsed: 0,
+            next_survival_spawn: 0,
+            intro_left: 0,
+            touching: HashSet::new(),
+            contacts: vec![],
+            demo: false,
+        }
+    }
+
+    /// Simulates `ticks` ticks of `game`.
+    fn run_ticks(game: &mut GameState, ticks: usize, settings: &Settings, rng: &mut StdRng) {
+        let mut log = EventLog::disabled();
+        for _ in 0..ticks {
+            game.update(bounds(), settings, rng, &mut log);
+        }
+    }
+
+    fn asteroids(game: &GameState) -> Vec<&Entity> {
+        game.entities
+            .iter()
+            .filter(|entity| matches!(entity.kind, EntityKind::Asteroid { .. }))
+            .collect()
+    }
+
+    /// A small asteroid out of the way in the top left corner, so that destroying everything
+    /// else doesn't start the next wave.
+    fn bystander(rng: &mut StdRng) -> Entity {
+        asteroid_at(1, Vector::new(20.0, 20.0), rng)
+    }
+
+    /// A human player at `position`, flown with the first control scheme.
+    fn player_at(position: Vector, lives: u32) -> Entity {
+        new_player(
+            position,
+            SHIP_HULLS[0].clone(),
+            lives,
+            Some(&CONTROL_SCHEMES[0]),
+        )
+    }
+
+    fn key_event(keycode: Keycode, down: bool) -> Event {
+        if down {
+            Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(keycode),
+                scancode: None,
+                keymod: sdl2::keyboard::Mod::NOMOD,
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(keycode),
+                scancode: None,
+                keymod: sdl2::keyboard::Mod::NOMOD,
+                repeat: false,
+            }
+        }
+    }
+
+    /// A player in the middle of the screen, charging with right shift and firing with space.
+    fn player() -> Entity {
+        Entity {
+            id: next_entity_id(),
+            color: None,
+            scale: 1.0,
+            sprite_verts: Some(Polygon {
+                verts: SHIP_HULLS[0].clone(),
+            }),
+            sprite_details: vec![],
+            bounding: Some(Bounding::CyclicTriangles {
+                verts: SHIP_HULLS[0].clone(),
+            }),
+            wrap: WrappingBehavior::Yes,
+            body: Body {
+                position: Vector { x: 400.0, y: 300.0 },
+                has_drag: true,
+                ..Default::default()
+            },
+            kind: EntityKind::Player {
+                accelerate: None,
+                turn_right: None,
+                turn_left: None,
+                fire: Some(Keycode::Space),
+                charge: Some(Keycode::RShift),
+                charge_frames: None,
+                cycle_weapon: Some(Keycode::Q),
+                weapon: Weapon::Standard,
+                lives: 3,
+                spawn_position: Vector { x: 400.0, y: 300.0 },
+                invulnerable: 0,
+                stunned: 0,
+                magnet: None,
+                magnet_active: false,
+                cpu: false,
+                bomb: Some(Keycode::B),
+                bombs: STARTING_BOMBS,
+                detonating: false,
+                cooldown: 0,
+                idle_frames: 0,
+                firing: false,
+                energy: MAX_ENERGY,
+                hyperspace: None,
+                hyperspace_cooldown: 0,
+                jumping: false,
+                warp: 0,
+            },
+            on_step: None,
+            age: 0,
+        }
+    }
+
+    /// Holds the charge key for `frames` frames, then releases it.
+    fn charge_for(player: &mut Entity, frames: u64, settings: &Settings) -> Vec<Entity> {
+        assert!(player
+            .handle_event(&key_event(Keycode::RShift, true), settings)
+            .is_empty());
+        for _ in 0..frames {
+            player.step(bounds(), settings);
+        }
+        player.handle_event(&key_event(Keycode::RShift, false), settings)
+    }
+
+    #[test]
+    fn full_charge_fires_a_charged_bullet() {
+        let settings = Settings::default();
+        let mut player = player();
+        let fired = charge_for(&mut player, settings.frames(CHARGE_SECONDS), &settings);
+        assert_eq!(fired.len(), 1);
+        assert!(matches!(
+            fired[0].kind,
+            EntityKind::Bullet { charged: true, .. }
+        ));
+    }
+
+    #[test]
+    fn releasing_the_charge_early_fizzles() {
+        let settings = Settings::default();
+        let mut player = player();
+        let full_charge = settings.frames(CHARGE_SECONDS);
+        assert!(charge_for(&mut player, full_charge - 1, &settings).is_empty());
+        // The charge starts over from nothing
+        assert!(charge_for(&mut player, 1, &settings).is_empty());
+    }
+
+    #[test]
+    fn cycling_to_piercing_fires_piercing_bullets() {
+        let settings = Settings::default();
+        let mut player = player();
+        player.handle_event(&key_event(Keycode::Q, true), &settings);
+        let fired = player.handle_event(&key_event(Keycode::Space, true), &settings);
+        assert_eq!(fired.len(), 1);
+        assert!(matches!(
+            fired[0].kind,
+            EntityKind::Bullet { pierces, .. } if pierces == PIERCE_COUNT
+        ));
+    }
+
+    #[test]
+    fn pierced_bullet_passes_through_asteroids_for_a_while() {
+        let asteroid = new_asteroid(
+            2,
+            Body::default(),
+            &AsteroidShapeTable::default(),
+            &mut StdRng::seed_from_u64(0),
+        );
+        let settings = Settings::default();
+        let mut bullet = fire_bullet(
+            &Body::default(),
+            PlayerId::MAX,
+            None,
+            false,
+            PIERCE_COUNT,
+            &settings,
+        );
+        assert!(bullet.collides_with(&asteroid, &settings));
+
+        pierce(&mut bullet);
+        assert!(matches!(
+            bullet.kind,
+            EntityKind::Bullet { pierces, .. } if pierces == PIERCE_COUNT - 1
+        ));
+        for _ in 0..PIERCE_INTANGIBLE_FRAMES {
+            assert!(!bullet.collides_with(&asteroid, &settings));
+            bullet.step(bounds(), &settings);
+        }
+        assert!(bullet.collides_with(&asteroid, &settings));
+    }
+
+    #[test]
+    fn lead_target_head_on() {
+        // Closing at 1 + 4 pixels per frame, they meet after 20 frames
+        let aim = lead_target(
+            Vector::ZERO,
+            Vector::new(100.0, 0.0),
+            Vector::new(-1.0, 0.0),
+            4.0,
+        );
+        assert!((aim.unwrap() - Vector::new(80.0, 0.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn lead_target_crossing() {
+        // A 3-4-5 triangle: after 25 frames the target has moved 75 and the bullet 125
+        let aim = lead_target(
+            Vector::ZERO,
+            Vector::new(0.0, -100.0),
+            Vector::new(3.0, 0.0),
+            5.0,
+        );
+        assert!((aim.unwrap() - Vector::new(75.0, -100.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn lead_target_unsolvable() {
+        // Fleeing faster than the bullet flies
+        let aim = lead_target(
+            Vector::ZERO,
+            Vector::new(100.0, 0.0),
+            Vector::new(5.0, 0.0),
+            4.0,
+        );
+        assert_eq!(aim, None);
+        // As fast as the bullet, so it never gets any closer
+        let aim = lead_target(
+            Vector::ZERO,
+            Vector::new(100.0, 0.0),
+            Vector::new(4.0, 0.0),
+            4.0,
+        );
+        assert_eq!(aim, None);
+    }
+
+    /// An asteroid of `size` at rest at `position`.
+    fn asteroid_at(size: usize, position: Vector, rng: &mut StdRng) -> Entity {
+        new_asteroid(
+            size,
+            Body {
+                position,
+                ..Default::default()
+            },
+            &AsteroidShapeTable::default(),
+            rng,
+        )
+    }
+
+    #[test]
+    fn nearest_across_seam() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // 750 pixels away directly, but only 50 across the left edge
+        let across = asteroid_at(1, Vector::new(775.0, 300.0), &mut rng);
+        let direct = asteroid_at(1, Vector::new(225.0, 300.0), &mut rng);
+        let from = Vector::new(25.0, 300.0);
+        let candidates = [across, direct];
+
+        let found = nearest(from, candidates.iter(), bounds(), true).unwrap();
+        assert_eq!(found.body.position, candidates[0].body.position);
+        let found = nearest(from, candidates.iter(), bounds(), false).unwrap();
+        assert_eq!(found.body.position, candidates[1].body.position);
+    }
+
+    #[test]
+    fn toroidal_delta_across_seam() {
+        let delta = toroidal_delta(Vector::new(25.0, 590.0), Vector::new(775.0, 10.0), bounds());
+        assert_eq!(delta, Vector::new(-50.0, 20.0));
+    }
+
+    #[test]
+    fn hard_waves_are_faster_than_easy() {
+        for wave in 1..=10 {
+            let speeds = |difficulty: &Difficulty| {
+                let mut rng = StdRng::seed_from_u64(0);
+                spawn_wave(
+                    wave,
+                    difficulty,
+                    &AsteroidShapeTable::default(),
+                    bounds(),
+                    &[],
+                    &mut rng,
+                )
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Asteroid { .. }))
+                .map(|asteroid| asteroid.body.velocity.length())
+                .collect::<Vec<_>>()
+            };
+            let slowest_hard = speeds(&Difficulty::HARD)
+                .into_iter()
+                .fold(Float::INFINITY, Float::min);
+            let fastest_easy = speeds(&Difficulty::EASY).into_iter().fold(0.0, Float::max);
+            assert!(slowest_hard > fastest_easy, "wave {wave}");
+        }
+    }
+
+    #[test]
+    fn waves_start_off_screen_with_their_count_and_size() {
+        let play_area = bounds().size();
+        let difficulty = Difficulty::NORMAL;
+        let mut rng = StdRng::seed_from_u64(0);
+        for wave in 1..=5 {
+            let entities = spawn_wave(
+                wave,
+                &difficulty,
+                &AsteroidShapeTable::default(),
+                bounds(),
+                &[],
+                &mut rng,
+            );
+            let mut asteroids = 0;
+            let mut bosses = 0;
+            for entity in &entities {
+                match entity.kind {
+                    EntityKind::Asteroid { size, .. } => {
+                        assert_eq!(size, difficulty.max_asteroid_size(wave));
+                        asteroids += 1;
+                    }
+                    EntityKind::Boss { .. } => bosses += 1,
+                    _ => panic!("waves are only asteroids and bosses"),
+                }
+                let Vector { x, y } = entity.body.position;
+                assert!(!(0.0..=play_area.x).contains(&x) || !(0.0..=play_area.y).contains(&y));
+            }
+            assert_eq!(asteroids, difficulty.asteroid_count(wave));
+            assert_eq!(bosses, usize::from(wave == BOSS_WAVE_INTERVAL));
+        }
+    }
+
+    #[test]
+    fn killed_player_respawns_invulnerable_until_out_of_lives() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut player = player();
+        player.body.position = Vector::new(100.0, 100.0);
+        let asteroid = asteroid_at(1, player.body.position, &mut rng);
+        assert!(player.collides_with(&asteroid, &settings));
+
+        let (result, debris) = kill_player(&mut player, &settings, &mut rng);
+        assert!(matches!(result, StepResult::None));
+        assert!(!debris.is_empty());
+        assert_eq!(player.body.position, Vector::new(400.0, 300.0));
+        assert!(matches!(player.kind, EntityKind::Player { lives: 2, .. }));
+        assert!(!player.collides_with(&asteroid, &settings));
+        for _ in 0..settings.frames(RESPAWN_INVULNERABLE_SECONDS) {
+            player.step(bounds(), &settings);
+        }
+        assert!(player.collides_with(&asteroid, &settings));
+
+        for _ in 1..2 {
+            assert!(matches!(
+                kill_player(&mut player, &settings, &mut rng).0,
+                StepResult::None
+            ));
+        }
+        assert!(matches!(
+            kill_player(&mut player, &settings, &mut rng).0,
+            StepResult::RemoveEntity(RemoveReason::Destroyed)
+        ));
+    }
+
+    #[test]
+    fn video_init_error_suggests_a_cause() {
+        // What SDL reports when there is no display
+        let message = video_init_error("No available video device".to_owned());
+        assert!(message.contains("is a display available?"));
+        assert!(message.ends_with("No available video device"));
+    }
+
+    #[test]
+    fn ricochet_bullet_bounces_off_right_edge() {
+        let settings = Settings {
+            ricochet: true,
+            ..Settings::default()
+        };
+        let mut bullet = fire_bullet(&Body::default(), PlayerId::MAX, None, false, 0, &settings);
+        // Heading east from just short of the right edge, so the bullet crosses it on its first step
+        bullet.body.position = Vector::new(798.0, 300.0);
+        bullet.body.velocity = Vector::new(4.0, 0.0);
+
+        bullet.step(bounds(), &settings);
+
+        assert_eq!(bullet.body.position, Vector::new(798.0, 300.0));
+        assert_eq!(bullet.body.velocity, Vector::new(-4.0, 0.0));
+        assert!(matches!(
+            bullet.kind,
+            EntityKind::Bullet { bounces, .. } if bounces == RICOCHET_BOUNCES - 1
+        ));
+    }
+
+    #[test]
+    fn bumper_knocks_player_away_and_stuns() {
+        let settings = Settings {
+            bumper: true,
+            ..Settings::default()
+        };
+        let mut player = player();
+        player.body.accelerating = true;
+        let mut unbumped = player.clone();
+        // The asteroid is below, so the player is knocked up
+        bump_player(&mut player, Vector::new(400.0, 350.0), bounds(), &settings);
+        assert_eq!(player.body.velocity, Vector::new(0.0, -BUMPER_KNOCKBACK));
+
+        // Thrust does nothing while stunned
+        for _ in 0..settings.frames(BUMPER_STUN_SECONDS) {
+            let velocity = player.body.velocity;
+            player.step(bounds(), &settings);
+            assert_eq!(player.body.velocity, velocity * 0.99);
+        }
+        unbumped.step(bounds(), &settings);
+        let velocity = player.body.velocity;
+        player.step(bounds(), &settings);
+        let thrust = player.body.velocity - velocity * 0.99;
+        assert!((thrust - unbumped.body.velocity).length() < 1e-4);
+    }
+
+    #[test]
+    fn same_seed_spawns_the_same_wave() {
+        let wave = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            spawn_wave(
+                3,
+                &Difficulty::NORMAL,
+                &AsteroidShapeTable::default(),
+                bounds(),
+                &[],
+                &mut rng,
+            )
+            .iter()
+            .map(|asteroid| (asteroid.body.position, asteroid.body.velocity))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(wave(1), wave(1));
+        assert_ne!(wave(1), wave(2));
+    }
+
+    #[test]
+    fn smaller_asteroids_are_worth_more() {
+        assert!(asteroid_points(1) > asteroid_points(2));
+        assert!(asteroid_points(2) > asteroid_points(3));
+    }
+
+    #[test]
+    fn collision_across_seam() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // 790 pixels apart directly, but only 10 across the left edge
+        let mut left = asteroid_at(1, Vector::new(5.0, 300.0), &mut rng);
+        let mut right = asteroid_at(1, Vector::new(795.0, 300.0), &mut rng);
+        assert!(left.collision(&right, bounds()).is_some());
+        assert!(right.collision(&left, bounds()).is_some());
+
+        // Not yet wrapping, so they really are apart
+        left.wrap = WrappingBehavior::OnceOnScreen;
+        right.wrap = WrappingBehavior::OnceOnScreen;
+        assert!(left.collision(&right, bounds()).is_none());
+    }
+
+    #[test]
+    fn magnet_pulls_only_within_its_cone() {
+        let player = Vector::new(400.0, 300.0);
+        // Facing north, with the powerup dead ahead
+        let pull = magnet_pull(player, 0.0, Vector::new(400.0, 200.0), bounds()).unwrap();
+        assert!(pull.y > 0.0 && pull.x.abs() < 1e-6);
+        // Behind, out to the side, and out of reach
+        for powerup in [
+            Vector::new(400.0, 400.0),
+            Vector::new(500.0, 300.0),
+            Vector::new(400.0, 300.0 - MAGNET_RADIUS - 10.0),
+        ] {
+            assert_eq!(magnet_pull(player, 0.0, powerup, bounds()), None);
+        }
+    }
+
+    #[test]
+    fn spread_fires_configured_pellets_across_its_arc() {
+        let settings = Settings {
+            spread_pellets: 7,
+            spread_angle: 0.6,
+            ..Settings::default()
+        };
+        let mut player = player();
+        player.body.rotation = 1.0;
+        if let EntityKind::Player { weapon, .. } = &mut player.kind {
+            *weapon = Weapon::Spread;
+        }
+
+        let bullets = player.handle_event(&key_event(Keycode::Space, true), &settings);
+
+        assert_eq!(bullets.len(), 7);
+        let offsets: Vec<Float> = bullets
+            .iter()
+            .map(|bullet| bullet.body.rotation - 1.0)
+            .collect();
+        assert!((offsets[0] + 0.3).abs() < 1e-4);
+        assert!((offsets[6] - 0.3).abs() < 1e-4);
+        assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+        // Reloading
+        assert!(player
+            .handle_event(&key_event(Keycode::Space, true), &settings)
+            .is_empty());
+    }
+
+    #[test]
+    fn laser_hits_nearest_asteroid_ahead() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let behind = asteroid_at(1, Vector::new(400.0, 360.0), &mut rng);
+        let far = asteroid_at(1, Vector::new(400.0, 100.0), &mut rng);
+        let near = asteroid_at(1, Vector::new(400.0, 200.0), &mut rng);
+        let entities = [behind, far, near];
+        let shooter = Body {
+            position: Vector::new(400.0, 300.0),
+            ..Default::default()
+        };
+
+        let (start, end, hit) = laser_beam(&shooter, &entities, bounds());
+
+        assert_eq!(hit.map(|(index, _)| index), Some(2));
+        // Stopping at the near side of the asteroid it hit
+        assert!(start.y > end.y && end.y > 200.0);
+    }
+
+    #[test]
+    fn laser_wraps_across_edges_and_stops_at_walls() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Just across the top edge from the shooter, by way of the bottom
+        let entities = [asteroid_at(1, Vector::new(400.0, 550.0), &mut rng)];
+        let shooter = Body {
+            position: Vector::new(400.0, 50.0),
+            ..Default::default()
+        };
+
+        let (_, end, hit) = laser_beam(&shooter, &entities, bounds());
+        assert_eq!(hit.map(|(index, _)| index), Some(0));
+        assert!(end.y < 0.0 && end.y > -50.0);
+
+        let walled = Bounds::new(UVec2::new(800, 600), WrapAxes::preset("none").unwrap());
+        let (_, end, hit) = laser_beam(&shooter, &entities, walled);
+        assert_eq!(hit, None);
+        assert_eq!(end, Vector::new(400.0, 0.0));
+    }
+
+    fn laser_player_at(position: Vector) -> Entity {
+        let mut player = player_at(position, 3);
+        if let EntityKind::Player { weapon, firing, .. } = &mut player.kind {
+            *weapon = Weapon::Laser;
+            *firing = true;
+        }
+        player
+    }
+
+    #[test]
+    fn laser_passes_through_protected_asteroids() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut protected = asteroid_at(1, Vector::new(400.0, 200.0), &mut rng);
+        if let EntityKind::Asteroid {
+            spawn_protection, ..
+        } = &mut protected.kind
+        {
+            *spawn_protection = 10;
+        }
+        let entities = [
+            protected,
+            asteroid_at(1, Vector::new(400.0, 100.0), &mut rng),
+        ];
+        let shooter = Body {
+            position: Vector::new(400.0, 300.0),
+            ..Default::default()
+        };
+
+        let (_, _, hit) = laser_beam(&shooter, &entities, bounds());
+
+        assert_eq!(hit.map(|(index, _)| index), Some(1));
+    }
+
+    #[test]
+    fn laser_wears_down_tough_asteroids() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut asteroid = asteroid_at(3, Vector::new(400.0, 200.0), &mut rng);
+        if let EntityKind::Asteroid { hits_remaining, .. } = &mut asteroid.kind {
+            *hits_remaining = 3;
+        }
+        let asteroid_id = asteroid.id;
+        let mut game = game_with(vec![asteroid, laser_player_at(Vector::new(400.0, 300.0))]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+
+        let asteroid = &game.entities[index_of(&game.entities, asteroid_id).unwrap()];
+        let EntityKind::Asteroid { hits_remaining, .. } = asteroid.kind else {
+            unreachable!()
+        };
+        assert_eq!(hits_remaining, 2);
+    }
+
+    #[test]
+    fn laser_hits_a_boss_past_its_destroyed_zones() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let mut boss = new_boss(ship_body(center, 0.0));
+        // The beam passes through the destroyed zone between east and south on its way to the
+        // first zone
+        boss.kind = EntityKind::Boss {
+            zones: [BOSS_ZONE_HEALTH, 0, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH],
+        };
+        let boss_id = boss.id;
+        let mut game = game_with(vec![
+            boss,
+            laser_player_at(center + Vector::new(30.0, 150.0)),
+        ]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+
+        let boss = &game.entities[index_of(&game.entities, boss_id).unwrap()];
+        let EntityKind::Boss { zones } = boss.kind else {
+            unreachable!()
+        };
+        assert_eq!(
+            zones,
+            [BOSS_ZONE_HEALTH - 1, 0, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH]
+        );
+    }
+
+    fn energy_of(player: &Entity) -> Float {
+        match player.kind {
+            EntityKind::Player { energy, .. } => energy,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn energy_blocks_firing_until_regenerated() {
+        let settings = Settings::default();
+        let mut player = player();
+        if let EntityKind::Player { energy, .. } = &mut player.kind {
+            *energy = 0.0;
+        }
+        let fire = key_event(Keycode::Space, true);
+        assert!(player.handle_event(&fire, &settings).is_empty());
+
+        let regen_frames = settings.frames(ENERGY_REGEN_SECONDS);
+        for _ in 0..regen_frames - 1 {
+            player.step(bounds(), &settings);
+        }
+        assert!(energy_of(&player) < MAX_ENERGY);
+        player.step(bounds(), &settings);
+        assert!((energy_of(&player) - MAX_ENERGY).abs() < 1e-3);
+        assert_eq!(player.handle_event(&fire, &settings).len(), 1);
+        let spent = MAX_ENERGY - energy_of(&player);
+        assert!((spent - Weapon::Standard.energy_cost()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn boss_zones_are_hit_only_while_they_last() {
+        let center = Vector::new(400.0, 300.0);
+        let mut boss = new_boss(Body {
+            position: center,
+            ..Default::default()
+        });
+        // In the first zone, between north and east
+        let in_first_zone = center + Vector::new(30.0, -30.0);
+        assert_eq!(boss_zone_at(&boss, in_first_zone, bounds()), Some(0));
+        // Between south and west
+        let in_third_zone = center + Vector::new(-30.0, 30.0);
+        assert_eq!(boss_zone_at(&boss, in_third_zone, bounds()), Some(2));
+        assert_eq!(
+            boss_zone_at(&boss, center + Vector::new(200.0, 0.0), bounds()),
+            None
+        );
+
+        boss.kind = EntityKind::Boss {
+            zones: [0, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH],
+        };
+        assert_eq!(boss_zone_at(&boss, in_first_zone, bounds()), None);
+    }
+
+    #[test]
+    fn cpu_adds_a_player_without_keys() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let cpu = |settings: &Settings, rng: &mut StdRng| {
+            initial_entities(settings, bounds(), rng)
+                .iter()
+                .filter(|entity| {
+                    matches!(
+                        entity.kind,
+                        EntityKind::Player {
+                            cpu: true,
+                            fire: None,
+                            ..
+                        }
+                    )
+                })
+                .count()
+        };
+        assert_eq!(cpu(&Settings::default(), &mut rng), 0);
+        let settings = Settings {
+            cpu_player: true,
+            ..Settings::default()
+        };
+        assert_eq!(cpu(&settings, &mut rng), 1);
+    }
+
+    #[test]
+    fn scaling_an_asteroid_doubles_its_bounding_triangles() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        let reach = |asteroid: &Entity| {
+            asteroid
+                .bounding_triangles()
+                .flatten()
+                .map(|point| point.distance(asteroid.body.position))
+                .fold(0.0, Float::max)
+        };
+        let unscaled = reach(&asteroid);
+        asteroid.scale = 2.0;
+        assert!((reach(&asteroid) - unscaled * 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bomb_sets_off_a_shockwave_that_grows_and_fades() {
+        let settings = Settings::default();
+        let mut player = player();
+        let mut new_entities = collect_powerup(&mut player, PowerupEffect::Bomb);
+        assert_eq!(new_entities.len(), 1);
+        let mut shockwave = new_entities.pop().unwrap();
+        assert_eq!(shockwave.body.position, player.body.position);
+
+        let frames = (SHOCKWAVE_MAX_RADIUS / SHOCKWAVE_GROWTH) as usize;
+        for frame in 1..frames {
+            assert!(matches!(
+                shockwave.step(bounds(), &settings),
+                StepResult::None
+            ));
+            assert_eq!(shockwave.scale, frame as Float * SHOCKWAVE_GROWTH);
+        }
+        assert!(matches!(
+            shockwave.step(bounds(), &settings),
+            StepResult::RemoveEntity(RemoveReason::Expired)
+        ));
+    }
+
+    #[test]
+    fn bomb_key_detonates_until_bombs_run_out() {
+        let settings = Settings::default();
+        let mut player = player();
+        for bombs_left in (0..STARTING_BOMBS).rev() {
+            player.handle_event(&key_event(Keycode::B, true), &settings);
+            let EntityKind::Player {
+                bombs, detonating, ..
+            } = &mut player.kind
+            else {
+                unreachable!()
+            };
+            assert_eq!(*bombs, bombs_left);
+            // The main loop sets the bomb off, clearing the flag
+            assert!(std::mem::take(detonating));
+        }
+        player.handle_event(&key_event(Keycode::B, true), &settings);
+        assert!(matches!(
+            player.kind,
+            EntityKind::Player {
+                bombs: 0,
+                detonating: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn ids_find_entities_after_others_are_swap_removed() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut entities = (0..5)
+            .map(|i| asteroid_at(1, Vector::new(100.0 * i as Float, 100.0), &mut rng))
+            .collect::<Vec<_>>();
+        let positions = entities
+            .iter()
+            .map(|entity| (entity.id, entity.body.position))
+            .collect::<Vec<_>>();
+
+        let (first, third) = remove_pair(&mut entities, 0, 2);
+        assert_eq!(first.id, positions[0].0);
+        assert_eq!(third.id, positions[2].0);
+        for &(id, position) in &[positions[1], positions[3], positions[4]] {
+            let index = index_of(&entities, id).unwrap();
+            assert_eq!(entities[index].body.position, position);
+        }
+        assert_eq!(index_of(&entities, first.id), None);
+    }
+
+    #[test]
+    fn collision_pairs_yield_their_events_in_either_order() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let origin = Vector::ZERO;
+        let body = Body::default();
+        let asteroid = asteroid_at(1, origin, &mut rng);
+        let boss = new_boss(body);
+        let bullet = new_bullet(body, PlayerId::MAX, None, BULLET_TTL);
+        let player = player();
+        let powerup = new_powerup(body, PowerupEffect::ExtraLife, 60);
+
+        let cases = [
+            (
+                &asteroid,
+                &bullet,
+                CollisionEvent::AsteroidHitByBullet {
+                    asteroid_id: asteroid.id,
+                    bullet_id: bullet.id,
+                },
+            ),
+            (
+                &boss,
+                &bullet,
+                CollisionEvent::BossHitByBullet {
+                    boss_id: boss.id,
+                    bullet_id: bullet.id,
+                },
+            ),
+            (
+                &player,
+                &asteroid,
+                CollisionEvent::PlayerDied {
+                    player_id: player.id,
+                },
+            ),
+            (
+                &player,
+                &boss,
+                CollisionEvent::PlayerDied {
+                    player_id: player.id,
+                },
+            ),
+            (
+                &player,
+                &bullet,
+                CollisionEvent::PlayerShot {
+                    player_id: player.id,
+                    bullet_id: bullet.id,
+                },
+            ),
+            (
+                &player,
+                &powerup,
+                CollisionEvent::PowerupCollected {
+                    player_id: player.id,
+                    powerup_id: powerup.id,
+                },
+            ),
+        ];
+        for (a, b, event) in cases {
+            assert_eq!(collision_event(a, b, &settings), Some(event));
+            assert_eq!(collision_event(b, a, &settings), Some(event));
+        }
+        assert_eq!(collision_event(&asteroid, &asteroid, &settings), None);
+        assert_eq!(collision_event(&bullet, &powerup, &settings), None);
+
+        let bumper = Settings {
+            bumper: true,
+            ..Settings::default()
+        };
+        assert_eq!(
+            collision_event(&asteroid, &player, &bumper),
+            Some(CollisionEvent::PlayerBumped {
+                player_id: player.id,
+                asteroid_id: asteroid.id,
+            })
+        );
+    }
+
+    #[test]
+    fn builder_matches_hand_written_entity() {
+        let body = Body {
+            position: Vector::new(10.0, 20.0),
+            velocity: Vector::new(1.0, -1.0),
+            rotation: 0.5,
+            has_drag: true,
+            ..Default::default()
+        };
+        let kind = EntityKind::Boss {
+            zones: [BOSS_ZONE_HEALTH; BOSS_ZONES],
+        };
+        let built = Entity::builder(kind)
+            .body(body)
+            .shape(BOSS_VERTS)
+            .scale(2.0)
+            .build();
+        let literal = Entity {
+            id: next_entity_id(),
+            body,
+            wrap: WrappingBehavior::Yes,
+            sprite_verts: Some(Polygon { verts: BOSS_VERTS }),
+            sprite_details: vec![],
+            color: None,
+            bounding: Some(Bounding::CyclicTriangles { verts: BOSS_VERTS }),
+            scale: 2.0,
+            kind,
+            on_step: None,
+            age: 0,
+        };
+
+        assert_ne!(built.id, literal.id);
+        let (a, b) = (&built.body, &literal.body);
+        assert_eq!(
+            (a.position, a.velocity, a.rotation),
+            (b.position, b.velocity, b.rotation)
+        );
+        assert_eq!(
+            (a.has_drag, a.accelerating, a.turning_left, a.turning_right),
+            (b.has_drag, b.accelerating, b.turning_left, b.turning_right)
+        );
+        assert!(matches!(built.wrap, WrappingBehavior::Yes));
+        assert_eq!(
+            built
+                .sprite_verts
+                .as_ref()
+                .map(|sprite| sprite.verts.to_vec()),
+            literal
+                .sprite_verts
+                .as_ref()
+                .map(|sprite| sprite.verts.to_vec())
+        );
+        assert!(built.bounding_triangles().eq(literal.bounding_triangles()));
+        assert_eq!(built.scale, literal.scale);
+        assert_eq!(format!("{:?}", built.kind), format!("{:?}", literal.kind));
+    }
+
+    #[test]
+    fn new_bullet_has_bullet_shape_and_kind() {
+        let body = Body {
+            position: Vector::new(50.0, 60.0),
+            prev_position: Vector::new(40.0, 60.0),
+            velocity: Vector::new(4.0, 0.0),
+            has_drag: true,
+            accelerating: true,
+            ..Default::default()
+        };
+        let bullet = new_bullet(body, 7, Some(Color::GREEN), 30);
+        assert_eq!(
+            bullet
+                .sprite_verts
+                .as_ref()
+                .map(|sprite| sprite.verts.to_vec()),
+            Some(BULLET_VERTS.to_vec())
+        );
+        let (
+            Some(Bounding::Triangles { triangles }),
+            Bounding::Triangles {
+                triangles: expected,
+            },
+        ) = (&bullet.bounding, BULLET_BOUNDS)
+        else {
+            panic!("bullets are bounded by their own triangles");
+        };
+        assert_eq!(triangles.to_vec(), expected.to_vec());
+        assert!(matches!(
+            bullet.kind,
+            EntityKind::Bullet {
+                owner: 7,
+                ttl: 30,
+                charged: false,
+                pierces: 0,
+                intangible: 0,
+                bounces: RICOCHET_BOUNCES,
+            }
+        ));
+        assert_eq!(bullet.color, Some(Color::GREEN));
+        // Bullets keep their shooter's motion, but start without a trail and never slow down
+        assert_eq!(bullet.body.position, body.position);
+        assert_eq!(bullet.body.prev_position, body.position);
+        assert_eq!(bullet.body.velocity, body.velocity);
+        assert!(!bullet.body.has_drag && !bullet.body.accelerating);
+    }
+
+    #[test]
+    fn bullet_trail_runs_from_previous_to_current_position() {
+        let settings = Settings::default();
+        let start = Vector::new(100.0, 100.0);
+        let mut bullet = new_bullet(
+            Body {
+                position: start,
+                velocity: Vector::new(3.0, 4.0),
+                ..Default::default()
+            },
+            PlayerId::MAX,
+            None,
+            BULLET_TTL,
+        );
+        assert_eq!(trail_segment(&bullet.body, bounds()), (start, start));
+
+        bullet.step(bounds(), &settings);
+        assert_eq!(
+            trail_segment(&bullet.body, bounds()),
+            (start, start + Vector::new(3.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn bullet_trail_stays_beside_bullet_across_seam() {
+        let body = Body {
+            position: Vector::new(2.0, 300.0),
+            prev_position: Vector::new(798.0, 300.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            trail_segment(&body, bounds()),
+            (Vector::new(-2.0, 300.0), Vector::new(2.0, 300.0))
+        );
+    }
+
+    #[test]
+    fn window_title_shows_score_and_wave() {
+        assert_eq!(
+            window_title("Asteroids", 0, 1),
+            "Asteroids - Score: 0 - Wave: 1"
+        );
+        assert_eq!(
+            window_title("Rocks", 12_340, 17),
+            "Rocks - Score: 12340 - Wave: 17"
+        );
+        let settings = Settings::from_args(["--title", "Rocks"].map(str::to_owned).into_iter());
+        assert_eq!(
+            window_title(&settings.unwrap().title, 0, 1),
+            "Rocks - Score: 0 - Wave: 1"
+        );
+    }
+
+    fn cooldown_of(player: &Entity) -> u64 {
+        let EntityKind::Player { cooldown, .. } = player.kind else {
+            unreachable!()
+        };
+        cooldown
+    }
+
+    #[test]
+    fn auto_fire_waits_a_cooldown_between_shots() {
+        let settings = Settings {
+            auto_fire: true,
+            ..Settings::default()
+        };
+        let mut auto = player();
+        assert_eq!(auto.fire(&settings).len(), 1);
+        let cooldown = settings.frames(AUTO_FIRE_COOLDOWN_SECONDS);
+        assert_eq!(cooldown_of(&auto), cooldown);
+        for _ in 0..cooldown {
+            auto.step(bounds(), &settings);
+        }
+        assert_eq!(cooldown_of(&auto), 0);
+
+        // Without auto-fire, each press fires straight away
+        let mut manual = player();
+        manual.fire(&Settings::default());
+        assert_eq!(cooldown_of(&manual), 0);
+    }
+
+    #[test]
+    fn four_players_spawn_apart_and_in_bounds() {
+        for size in [UVec2::new(800, 600), UVec2::new(120, 90)] {
+            let bounds = Bounds::new(size, WrapAxes::BOTH);
+            let positions = spawn_positions(4, bounds);
+            assert_eq!(positions.len(), 4);
+            for position in &positions {
+                assert!(bounds.contains_aabb(*position, *position), "{position}");
+            }
+            for (a, b) in positions.iter().tuple_combinations() {
+                assert!(a.distance(*b) > 1.0, "{a} and {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_towards_faces_the_way_ships_fire() {
+        for rotation in [0.0, 0.5, 2.0, -1.0, -3.0] {
+            let facing = rotation_matrix(rotation) * Vector::new(0.0, -1.0);
+            assert!((rotation_towards(facing) - rotation).abs() < 1e-4);
+            // Only the direction matters
+            assert!((rotation_towards(facing * 5.0) - rotation).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn horizontal_wrap_wraps_x_and_bounces_off_y() {
+        let settings = Settings::default();
+        let bounds = Bounds::new(UVec2::new(800, 600), WrapAxes { x: true, y: false });
+        let bullet = |position, velocity| {
+            new_bullet(
+                Body {
+                    position,
+                    velocity,
+                    ..Default::default()
+                },
+                PlayerId::MAX,
+                None,
+                BULLET_TTL,
+            )
+        };
+        let mut across = bullet(Vector::new(798.0, 300.0), Vector::new(4.0, 0.0));
+        let mut down = bullet(Vector::new(400.0, 598.0), Vector::new(0.0, 4.0));
+
+        across.step(bounds, &settings);
+        down.step(bounds, &settings);
+        assert_eq!(across.body.position, Vector::new(2.0, 300.0));
+        assert_eq!(across.body.velocity, Vector::new(4.0, 0.0));
+        assert_eq!(down.body.position, Vector::new(400.0, 598.0));
+        assert_eq!(down.body.velocity, Vector::new(0.0, -4.0));
+    }
+
+    #[test]
+    fn debris_keeps_its_ttl_and_dims_as_it_runs_out() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut debris = new_debris(Body::default(), 45, &mut rng);
+        assert!(matches!(
+            debris.kind,
+            EntityKind::Debris {
+                ttl: 45,
+                lifetime: 45
+            }
+        ));
+
+        let theme = Theme::default();
+        let mut brightness = |ttl| {
+            let EntityKind::Debris {
+                ttl: debris_ttl, ..
+            } = &mut debris.kind
+            else {
+                unreachable!()
+            };
+            *debris_ttl = ttl;
+            let color = theme.color_of(&debris.kind);
+            color.r as u32 + color.g as u32 + color.b as u32
+        };
+        let brightnesses = [45, 30, 15, 0].map(&mut brightness);
+        assert!(
+            brightnesses.windows(2).all(|pair| pair[0] > pair[1]),
+            "{brightnesses:?}"
+        );
+    }
+
+    #[test]
+    fn sprite_area_grows_with_the_square_of_scale() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        let unscaled = sprite_area(&asteroid);
+        asteroid.scale = 3.0;
+        assert!((sprite_area(&asteroid) - unscaled * 9.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn safe_spawns_keep_away_from_players() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let players = [Vector::new(10.0, 10.0), Vector::new(790.0, 300.0)];
+        for _ in 0..200 {
+            let position = safe_spawn_position(bounds(), &players, 150.0, &mut rng);
+            for player in players {
+                let distance = toroidal_delta(position, player, bounds()).length();
+                assert!(distance >= 150.0, "{position} is {distance} from {player}");
+            }
+        }
+    }
+
+    /// With the `f32` feature, checks a ship flies close enough to how it would in `f64`.
+    #[test]
+    // The casts only do anything with the `f32` feature
+    #[allow(clippy::unnecessary_cast)]
+    fn simulation_precision_matches_f64() {
+        let settings = Settings::default();
+        let mut player = player();
+        player.body.accelerating = true;
+        player.body.turning_left = true;
+        let frames = 120;
+        for _ in 0..frames {
+            player.step(bounds(), &settings);
+        }
+
+        // The same flight, worked through in f64
+        let turn_rate = std::f64::consts::TAU / (3.0 * settings.fps as f64);
+        let (mut position, mut velocity) = (glam::DVec2::new(400.0, 300.0), glam::DVec2::ZERO);
+        let mut rotation = 0.0_f64;
+        for _ in 0..frames {
+            velocity += glam::DVec2::new(-rotation.sin(), -rotation.cos()) * 0.1;
+            rotation = (rotation + turn_rate).rem_euclid(std::f64::consts::TAU);
+            velocity *= 0.99;
+            position += velocity;
+            position = glam::DVec2::new(position.x.rem_euclid(800.0), position.y.rem_euclid(600.0));
+        }
+
+        let simulated = player.body.position;
+        let simulated = glam::DVec2::new(simulated.x as f64, simulated.y as f64);
+        assert!(
+            simulated.distance(position) < 0.01,
+            "{simulated} vs {position}"
+        );
+        assert!((player.body.rotation as f64 - rotation).abs() < 1e-4);
+    }
+
+    #[test]
+    fn forward_follows_the_rotation_convention() {
+        assert_eq!(forward(0.0), Vector::new(0.0, -1.0));
+        for rotation in [0.0, 1.0, consts::FRAC_PI_2, 3.0, 5.5] {
+            let heading = forward(rotation);
+            assert!((heading.length() - 1.0).abs() < 1e-6);
+            let error = rotation_towards(heading) - rotation;
+            assert!(((error + consts::PI).rem_euclid(consts::TAU) - consts::PI).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn inverted_y_swaps_the_turning_keys() {
+        let controls = &CONTROL_SCHEMES[0];
+        let settings = Settings {
+            invert_y: true,
+            ..Settings::default()
+        };
+        let mut player = new_player(
+            Vector::new(400.0, 300.0),
+            SHIP_HULLS[0].clone(),
+            3,
+            Some(controls),
+        );
+        player.handle_event(&key_event(controls.turn_left, true), &settings);
+        assert!(player.body.turning_right && !player.body.turning_left);
+        player.handle_event(&key_event(controls.turn_left, false), &settings);
+        assert!(!player.body.turning_right);
+    }
+
+    #[test]
+    fn survival_interval_shrinks_with_elapsed_time() {
+        let intervals = (0..=600)
+            .map(|second| survival_spawn_interval(second as Float))
+            .collect::<Vec<_>>();
+        assert_eq!(intervals[0], SURVIVAL_START_INTERVAL);
+        assert!(intervals.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(intervals[60] < intervals[0]);
+        assert_eq!(intervals[600], SURVIVAL_MIN_INTERVAL);
+    }
+
+    #[test]
+    fn keys_pressed_together_all_register() {
+        let settings = Settings::default();
+        let controls = &CONTROL_SCHEMES[0];
+        let mut player = new_player(
+            Vector::new(400.0, 300.0),
+            SHIP_HULLS[0].clone(),
+            3,
+            Some(controls),
+        );
+        player.body.rotation = 1.0;
+        let mut fired = vec![];
+        for keycode in [
+            controls.accelerate,
+            controls.turn_left,
+            controls.turn_right,
+            controls.fire,
+        ] {
+            fired.extend(player.handle_event(&key_event(keycode, true), &settings));
+        }
+        assert!(player.body.accelerating);
+        assert!(player.body.turning_left && player.body.turning_right);
+        assert_eq!(fired.len(), 1);
+
+        // Turning both ways at once doesn't turn at all
+        player.step(bounds(), &settings);
+        assert_eq!(player.body.rotation, 1.0);
+    }
+
+    #[test]
+    fn players_with_different_hulls_differ_in_shape_and_bounds() {
+        let position = Vector::new(400.0, 300.0);
+        let first = new_player(position, SHIP_HULLS[0].clone(), 3, None);
+        let second = new_player(position, SHIP_HULLS[1].clone(), 3, None);
+        let verts = |player: &Entity| player.sprite_verts.as_ref().unwrap().verts.to_vec();
+        assert_ne!(verts(&first), verts(&second));
+        assert!(!first.bounding_triangles().eq(second.bounding_triangles()));
+        // Each is bounded by its own hull
+        for (player, hull) in [(&first, &SHIP_HULLS[0]), (&second, &SHIP_HULLS[1])] {
+            let Some(Bounding::CyclicTriangles { verts }) = &player.bounding else {
+                panic!("players are bounded by their hull");
+            };
+            assert_eq!(verts.to_vec(), hull.to_vec());
+        }
+    }
+
+    fn verts(points: &[(Float, Float)]) -> Verts {
+        Either::Right(points.iter().map(|&(x, y)| Vector::new(x, y)).collect())
+    }
+
+    #[test]
+    fn cyclic_bounding_rejects_too_few_verts() {
+        assert_eq!(
+            Bounding::cyclic(verts(&[])).err(),
+            Some(BoundingError::TooFewVerts(0))
+        );
+        assert_eq!(
+            Bounding::cyclic(verts(&[(0.0, 1.0), (1.0, 0.0)])).err(),
+            Some(BoundingError::TooFewVerts(2))
+        );
+    }
+
+    #[test]
+    fn cyclic_bounding_rejects_collinear_verts() {
+        for points in [
+            &[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (-3.0, -3.0)][..],
+            &[(5.0, 5.0), (5.0, 5.0), (5.0, 5.0)],
+        ] {
+            assert_eq!(
+                Bounding::cyclic(verts(points)).err(),
+                Some(BoundingError::Collinear),
+                "{points:?}"
+            );
+        }
+        assert!(Bounding::cyclic(verts(&[(0.0, -1.0), (1.0, 1.0), (-1.0, 1.0)])).is_ok());
+    }
+
+    #[test]
+    fn degenerate_triangles_never_collide() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let position = Vector::new(400.0, 300.0);
+        let asteroid = asteroid_at(2, position, &mut rng);
+        // A flat triangle straight through the asteroid, and a real one well clear of it
+        let flat = [
+            Vector::new(-50.0, 0.0),
+            Vector::new(0.0, 0.0),
+            Vector::new(50.0, 0.0),
+        ];
+        let clear = [
+            Vector::new(200.0, 0.0),
+            Vector::new(210.0, 0.0),
+            Vector::new(200.0, 10.0),
+        ];
+        let with_triangles = |triangles: Vec<[Vector; 3]>| {
+            Entity::builder(EntityKind::Debris {
+                ttl: 1,
+                lifetime: 1,
+            })
+            .body(ship_body(position, 0.0))
+            .bounding(Bounding::Triangles {
+                triangles: Either::Right(triangles.into()),
+            })
+            .build()
+        };
+
+        let degenerate = with_triangles(vec![flat, clear]);
+        assert!(degenerate.collision(&asteroid, bounds()).is_none());
+        assert!(asteroid.collision(&degenerate, bounds()).is_none());
+        // The same triangle with some area does collide
+        let mut solid = flat;
+        solid[1].y = 5.0;
+        assert!(with_triangles(vec![solid])
+            .collision(&asteroid, bounds())
+            .is_some());
+    }
+
+    #[test]
+    fn dampener_slows_an_idle_ship_faster_than_drag() {
+        let speed_after_coasting = |dampener| {
+            let settings = Settings {
+                dampener,
+                ..Settings::default()
+            };
+            let mut player = player();
+            player.body.velocity = Vector::new(3.0, 0.0);
+            for _ in 0..settings.frames(2.0) {
+                player.step(bounds(), &settings);
+            }
+            player.body.velocity.length()
+        };
+        let with_drag = speed_after_coasting(false);
+        let dampened = speed_after_coasting(true);
+        assert!(dampened < with_drag / 2.0, "{dampened} vs {with_drag}");
+    }
+
+    #[test]
+    fn phantom_triangles_are_whole_screens_away() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut asteroid = asteroid_at(2, Vector::new(795.0, 5.0), &mut rng);
+        asteroid.wrap = WrappingBehavior::Yes;
+        let triangles = asteroid.bounding_triangles().collect::<Vec<_>>();
+        let phantoms = asteroid.phantom_triangles(bounds()).collect::<Vec<_>>();
+
+        // Across the right edge, the top edge, and the corner between them
+        let offsets = [
+            Vector::new(-800.0, 0.0),
+            Vector::new(0.0, 600.0),
+            Vector::new(-800.0, 600.0),
+        ];
+        assert_eq!(phantoms.len(), triangles.len() * offsets.len());
+        for offset in offsets {
+            for triangle in &triangles {
+                let phantom = triangle.map(|point| point + offset);
+                assert!(phantoms.contains(&phantom), "{offset}");
+            }
+        }
+
+        asteroid.body.position = Vector::new(400.0, 300.0);
+        assert_eq!(asteroid.phantom_triangles(bounds()).count(), 0);
+    }
+
+    #[test]
+    fn custom_shape_table_changes_vert_count() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut shapes = AsteroidShapeTable::default();
+        shapes.set_from_arg("2:5:10:12").unwrap();
+        let vert_count = |size, rng: &mut StdRng| {
+            let asteroid = new_asteroid(size, Body::default(), &shapes, rng);
+            asteroid.sprite_verts.unwrap().verts.len()
+        };
+        assert_eq!(vert_count(2, &mut rng), 5);
+        assert_eq!(vert_count(1, &mut rng), 6);
+    }
+
+    /// A bullet at rest at `position`, fired by no player in particular.
+    fn bullet_at(position: Vector) -> Entity {
+        new_bullet(ship_body(position, 0.0), PlayerId::MAX, None, BULLET_TTL)
+    }
+
+    #[test]
+    fn asteroids_remaining_counts_only_asteroids_and_bosses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let position = Vector::new(400.0, 300.0);
+        let body = ship_body(position, 0.0);
+        let entities = vec![
+            asteroid_at(3, position, &mut rng),
+            asteroid_at(1, position, &mut rng),
+            new_boss(body),
+            new_debris(body, DEBRIS_TTL, &mut rng),
+            new_debris(body, DEBRIS_TTL, &mut rng),
+            bullet_at(position),
+            new_powerup(body, PowerupEffect::Bomb, 60),
+            new_shockwave(position),
+            player(),
+        ];
+        assert_eq!(asteroids_remaining(&entities), 3);
+        assert_eq!(asteroids_remaining(&entities[3..]), 0);
+    }
+
+    #[test]
+    fn bullets_take_muzzle_speed_and_scale_from_physics() {
+        let settings = Settings {
+            physics: physics::Physics {
+                muzzle_speed: 9.0,
+                bullet_scale: 2.0,
+                ..Default::default()
+            },
+            ..Settings::default()
+        };
+        let shooter = Body {
+            velocity: Vector::new(1.0, 2.0),
+            ..ship_body(Vector::new(400.0, 300.0), consts::FRAC_PI_2)
+        };
+        let bullet = fire_bullet(&shooter, 0, None, false, 0, &settings);
+        let relative = bullet.body.velocity - shooter.velocity;
+        assert!((relative - forward(shooter.rotation) * 9.0).length() < 1e-5);
+        assert_eq!(bullet.scale, 2.0);
+    }
+
+    #[test]
+    fn velocity_vector_points_along_the_velocity() {
+        let body = Body {
+            position: Vector::new(100.0, 200.0),
+            velocity: Vector::new(2.0, -1.0),
+            ..Default::default()
+        };
+        let (start, end) = velocity_vector(&body);
+        assert_eq!(start, body.position);
+        // Ten frames ahead
+        assert_eq!(end, Vector::new(120.0, 190.0));
+        // At rest, the line has no length
+        let (start, end) = velocity_vector(&Body::default());
+        assert_eq!(start, end);
+    }
+
+    #[test]
+    fn arena_walls_reflect_and_contain() {
+        let settings = Settings::default();
+        let arena = Bounds::new(UVec2::new(800, 600), WrapAxes::preset("none").unwrap());
+        for (position, velocity, bounced_position, bounced_velocity) in [
+            ((798.0, 300.0), (4.0, 1.0), (798.0, 301.0), (-4.0, 1.0)),
+            ((400.0, 2.0), (-1.0, -4.0), (399.0, 2.0), (-1.0, 4.0)),
+            ((1.0, 599.0), (-3.0, 3.0), (2.0, 598.0), (3.0, -3.0)),
+        ] {
+            let mut bullet = bullet_at(Vector::from(position));
+            bullet.body.velocity = Vector::from(velocity);
+            bullet.step(arena, &settings);
+            assert_eq!(bullet.body.position, Vector::from(bounced_position));
+            assert_eq!(bullet.body.velocity, Vector::from(bounced_velocity));
+        }
+    }
+
+    #[test]
+    fn players_start_with_the_configured_lives() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let settings = Settings {
+            players: 2,
+            cpu_player: true,
+            lives: 5,
+            ..Settings::default()
+        };
+        let entities = initial_entities(&settings, bounds(), &mut rng);
+        let lives = entities
+            .iter()
+            .filter_map(|entity| match entity.kind {
+                EntityKind::Player { lives, .. } => Some(lives),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lives, [5, 5, 5]);
+    }
+
+    #[test]
+    fn lerp_color_blends_and_clamps() {
+        let a = Color::RGBA(0, 100, 255, 255);
+        let b = Color::RGBA(255, 50, 0, 55);
+        assert_eq!(lerp_color(a, b, 0.0), a);
+        assert_eq!(lerp_color(a, b, 1.0), b);
+        // Halfway channels round to the nearest
+        assert_eq!(lerp_color(a, b, 0.5), Color::RGBA(128, 75, 128, 155));
+        assert_eq!(lerp_color(a, b, -1.0), a);
+        assert_eq!(lerp_color(a, b, 2.0), b);
+    }
+
+    #[test]
+    fn big_bang_bursts_from_the_center_while_players_look_on() {
+        let settings = Settings {
+            big_bang: true,
+            ..Settings::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let entities = initial_entities(&settings, bounds(), &mut rng);
+        for entity in &entities {
+            match entity.kind {
+                EntityKind::Asteroid { .. } => {
+                    assert_eq!(entity.body.position, bounds().size() / 2.0);
+                }
+                EntityKind::Player { invulnerable, .. } => {
+                    assert_eq!(invulnerable, intro_frames(&settings));
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(intro_frames(&settings), settings.frames(BIG_BANG_SECONDS));
+        assert_eq!(intro_frames(&Settings::default()), 0);
+    }
+
+    #[test]
+    fn initial_asteroids_start_apart_and_head_on_screen() {
+        let settings = Settings::default();
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let entities = initial_entities(&settings, bounds(), &mut rng);
+            let asteroids = entities
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Asteroid { .. }))
+                .map(|asteroid| &asteroid.body)
+                .collect::<Vec<_>>();
+            assert_eq!(asteroids.len(), 3, "seed {seed}");
+            for (i, a) in asteroids.iter().enumerate() {
+                for b in &asteroids[i + 1..] {
+                    assert_ne!(a.position, b.position, "seed {seed}");
+                }
+                // Just off-screen, and on it a little later
+                let later = a.position + a.velocity * 100.0;
+                assert!(
+                    later.cmpge(Vector::ZERO).all() && later.cmple(bounds().size()).all(),
+                    "seed {seed}: {:?} heading {:?}",
+                    a.position,
+                    a.velocity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn initial_asteroids_do_not_overlap() {
+        let settings = Settings::default();
+        let radius = |asteroid: &Entity| {
+            let (min, max) = asteroid.bounding_box();
+            (max - min).length() / 2.0
+        };
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let entities = initial_entities(&settings, bounds(), &mut rng);
+            let asteroids = entities
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Asteroid { .. }))
+                .collect::<Vec<_>>();
+            for (i, a) in asteroids.iter().enumerate() {
+                for b in &asteroids[i + 1..] {
+                    let distance = a.body.position.distance(b.body.position);
+                    assert!(distance >= radius(a) + radius(b), "seed {seed}: {distance}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn craters_are_the_same_for_the_same_seed() {
+        let lines = crater_lines(0, 40.0);
+        assert_eq!(lines.len(), CRATERS_PER_ASTEROID * 3);
+        assert_eq!(crater_lines(0, 40.0), lines);
+        assert_ne!(crater_lines(1, 40.0), lines);
+        // Well inside the asteroid
+        for (a, b) in lines {
+            assert!(a.length() <= 30.0 && b.length() <= 30.0, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn firing_then_updating_adds_a_bullet() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(400.0, 300.0), 3);
+        let player_id = player.id;
+        let mut game = game_with(vec![player, bystander(&mut rng)]);
+
+        game.handle_event(
+            &key_event(CONTROL_SCHEMES[0].fire, true),
+            &settings,
+            &mut EventLog::disabled(),
+        );
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let bullets = game
+            .entities
+            .iter()
+            .filter(|entity| matches!(entity.kind, EntityKind::Bullet { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(bullets.len(), 1);
+        assert!(matches!(bullets[0].kind, EntityKind::Bullet { owner, .. } if owner == player_id));
+        // Heading the way the ship faces, north
+        assert!(bullets[0].body.velocity.y < 0.0);
+        assert!(bullets[0].body.position.y < 300.0);
+    }
+
+    #[test]
+    fn charged_bullet_vaporizes_asteroid() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let target = asteroid_at(3, Vector::new(400.0, 300.0), &mut rng);
+        let bystander = bystander(&mut rng);
+        let bystander_id = bystander.id;
+        // Fired from just below, so the bullet starts on top of the asteroid
+        let shooter = ship_body(Vector::new(400.0, 320.0), 0.0);
+        let bullet = fire_bullet(&shooter, 0, None, true, 0, &settings);
+        let mut game = game_with(vec![target, bystander, bullet]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+
+        let ids: Vec<u64> = asteroids(&game)
+            .iter()
+            .map(|asteroid| asteroid.id)
+            .collect();
+        assert_eq!(ids, [bystander_id]);
+        assert_eq!(game.score, asteroid_points(3));
+    }
+
+    #[test]
+    fn piercing_bullet_splits_asteroids_in_a_line() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let near = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        let far = asteroid_at(2, Vector::new(400.0, 100.0), &mut rng);
+        let (near_id, far_id) = (near.id, far.id);
+        let shooter = ship_body(Vector::new(400.0, 320.0), 0.0);
+        let bullet = fire_bullet(&shooter, 0, None, false, PIERCE_COUNT, &settings);
+        let bullet_id = bullet.id;
+        let mut game = game_with(vec![near, far, bullet]);
+
+        run_ticks(&mut game, 80, &settings, &mut rng);
+
+        let remaining = asteroids(&game);
+        assert!(remaining
+            .iter()
+            .all(|asteroid| asteroid.id != near_id && asteroid.id != far_id));
+        assert_eq!(remaining.len(), 4);
+        assert_eq!(game.score, 2 * asteroid_points(2));
+        let bullet = &game.entities[index_of(&game.entities, bullet_id).unwrap()];
+        assert!(
+            matches!(bullet.kind, EntityKind::Bullet { pierces, .. } if pierces == PIERCE_COUNT - 2)
+        );
+    }
+
+    fn lives_of(game: &GameState, player_id: u64) -> Option<u32> {
+        let player = &game.entities[index_of(&game.entities, player_id)?];
+        match player.kind {
+            EntityKind::Player { lives, .. } => Some(lives),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn practice_players_survive_asteroids() {
+        for (practice, lives) in [(true, 3), (false, 2)] {
+            let settings = Settings {
+                practice,
+                ..Settings::default()
+            };
+            let mut rng = StdRng::seed_from_u64(0);
+            let position = Vector::new(400.0, 300.0);
+            let player = player_at(position, 3);
+            let player_id = player.id;
+            let asteroid = asteroid_at(2, position, &mut rng);
+            let mut game = game_with(vec![player, asteroid]);
+
+            run_ticks(&mut game, 1, &settings, &mut rng);
+
+            assert_eq!(
+                lives_of(&game, player_id),
+                Some(lives),
+                "practice: {practice}"
+            );
+        }
+    }
+
+    /// Somewhere for an `EventLog` to write to that a test can read back.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        /// The lines written so far that contain `pattern`.
+        fn lines_with(&self, pattern: &str) -> Vec<String> {
+            String::from_utf8(self.0.borrow().clone())
+                .unwrap()
+                .lines()
+                .filter(|line| line.contains(pattern))
+                .map(str::to_owned)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn split_is_logged_once_with_its_size() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        let asteroid_id = asteroid.id;
+        let shooter = ship_body(Vector::new(400.0, 320.0), 0.0);
+        let bullet = fire_bullet(&shooter, 0, None, false, 0, &settings);
+        let mut game = game_with(vec![asteroid, bullet]);
+        let buffer = SharedBuffer::default();
+        let mut log = EventLog::new(Box::new(buffer.clone()));
+
+        game.update(bounds(), &settings, &mut rng, &mut log);
+
+        let splits = buffer.lines_with("event=split");
+        assert_eq!(splits.len(), 1);
+        assert!(splits[0].contains(&format!(" id={asteroid_id} size=2 ")));
+    }
+
+    #[test]
+    fn shooting_every_piece_clears_the_field() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(100.0, 100.0), 3);
+        let player_id = player.id;
+        let asteroid = asteroid_at(3, Vector::new(400.0, 300.0), &mut rng);
+        let mut game = game_with(vec![player, asteroid]);
+        let mut seen = HashSet::new();
+
+        // Clearing the field starts the next wave
+        for _ in 0..1000 {
+            if game.wave > 1 {
+                break;
+            }
+            seen.extend(asteroids(&game).iter().map(|asteroid| asteroid.id));
+            // Whenever the last bullet is spent, shoot the oldest piece left from just below it
+            let in_flight = game
+                .entities
+                .iter()
+                .any(|entity| matches!(entity.kind, EntityKind::Bullet { .. }));
+            if !in_flight {
+                let target = asteroids(&game)
+                    .into_iter()
+                    .min_by_key(|asteroid| asteroid.id)
+                    .unwrap();
+                let shooter = ship_body(target.body.position + Vector::new(0.0, 20.0), 0.0);
+                let bullet = fire_bullet(&shooter, player_id, None, false, 0, &settings);
+                game.entities.push(bullet);
+            }
+            run_ticks(&mut game, 1, &settings, &mut rng);
+        }
+
+        assert_eq!(game.wave, 2);
+        assert_eq!(
+            game.score,
+            asteroid_points(3) + 2 * asteroid_points(2) + 4 * asteroid_points(1)
+        );
+        // The asteroid and all six of its pieces were shot
+        assert_eq!(seen.len(), 7);
+        // Only the next wave's arrivals are left, still off-screen
+        assert!(asteroids(&game).iter().all(|asteroid| {
+            !seen.contains(&asteroid.id) && matches!(asteroid.wrap, WrappingBehavior::OnceOnScreen)
+        }));
+        assert_eq!(lives_of(&game, player_id), Some(3));
+    }
+
+    #[test]
+    fn boss_survives_until_every_zone_is_destroyed() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let boss = new_boss(ship_body(center, 0.0));
+        let boss_id = boss.id;
+        // In the first zone, between north and east
+        let in_first_zone = center + Vector::new(30.0, -30.0);
+        let mut game = game_with(vec![boss, bullet_at(in_first_zone)]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+
+        let boss = &game.entities[index_of(&game.entities, boss_id).unwrap()];
+        let EntityKind::Boss { zones } = boss.kind else {
+            unreachable!()
+        };
+        assert_eq!(
+            zones,
+            [
+                BOSS_ZONE_HEALTH - 1,
+                BOSS_ZONE_HEALTH,
+                BOSS_ZONE_HEALTH,
+                BOSS_ZONE_HEALTH
+            ]
+        );
+        assert_eq!(game.score, 0);
+
+        // Down to its last hit
+        let index = index_of(&game.entities, boss_id).unwrap();
+        game.entities[index].kind = EntityKind::Boss {
+            zones: [1, 0, 0, 0],
+        };
+        game.entities.push(bullet_at(in_first_zone));
+        run_ticks(&mut game, 1, &settings, &mut rng);
+
+        assert_eq!(index_of(&game.entities, boss_id), None);
+        assert_eq!(game.score, BOSS_POINTS);
+    }
+
+    #[test]
+    fn boss_is_hit_by_a_bullet_fired_from_range() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let mut boss = new_boss(ship_body(center, 0.0));
+        // The bullet passes through the destroyed zone between east and south on its way to
+        // the first zone
+        boss.kind = EntityKind::Boss {
+            zones: [BOSS_ZONE_HEALTH, 0, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH],
+        };
+        let boss_id = boss.id;
+        // Well below the boss's east half, aiming up at it
+        let shooter = ship_body(center + Vector::new(30.0, 200.0), 0.0);
+        let bullet = fire_bullet(&shooter, PlayerId::MAX, None, false, 0, &settings);
+        let bullet_id = bullet.id;
+        let mut game = game_with(vec![boss, bullet]);
+
+        run_ticks(&mut game, BULLET_TTL as usize, &settings, &mut rng);
+
+        assert_eq!(index_of(&game.entities, bullet_id), None);
+        let boss = &game.entities[index_of(&game.entities, boss_id).unwrap()];
+        let EntityKind::Boss { zones } = boss.kind else {
+            unreachable!()
+        };
+        assert_eq!(
+            zones,
+            [BOSS_ZONE_HEALTH - 1, 0, BOSS_ZONE_HEALTH, BOSS_ZONE_HEALTH]
+        );
+    }
+
+    #[test]
+    fn shockwave_destroys_only_asteroids_inside_its_radius() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let inside = asteroid_at(1, center + Vector::new(0.0, 4.0), &mut rng);
+        let outside = asteroid_at(1, center + Vector::new(200.0, 0.0), &mut rng);
+        let outside_id = outside.id;
+        let mut game = game_with(vec![new_shockwave(center), inside, outside]);
+
+        // One step grows the shockwave to its first radius, past the inner asteroid only
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let remaining = asteroids(&game);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, outside_id);
+    }
+
+    #[test]
+    fn bomb_destroys_every_asteroid_on_screen_for_their_points() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(100.0, 100.0), 3);
+        let field = [
+            asteroid_at(1, Vector::new(600.0, 100.0), &mut rng),
+            asteroid_at(2, Vector::new(600.0, 450.0), &mut rng),
+            asteroid_at(3, Vector::new(250.0, 450.0), &mut rng),
+        ];
+        let field_ids = field.iter().map(|asteroid| asteroid.id).collect::<Vec<_>>();
+        let mut game = game_with([player].into_iter().chain(field).collect());
+
+        game.handle_event(
+            &key_event(CONTROL_SCHEMES[0].bomb, true),
+            &settings,
+            &mut EventLog::disabled(),
+        );
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert_eq!(
+            game.score,
+            asteroid_points(1) + asteroid_points(2) + asteroid_points(3)
+        );
+        // Clearing the field starts the next wave, whose asteroids are all new
+        assert_eq!(game.wave, 2);
+        assert!(asteroids(&game)
+            .iter()
+            .all(|asteroid| !field_ids.contains(&asteroid.id)));
+    }
+
+    /// Holds the first player's fire key in a game of just them and a bystander, returning
+    /// the ticks on which they fired again after the shot the key press itself fires.
+    fn ticks_firing_while_held(settings: &Settings, ticks: usize) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(400.0, 300.0), 3);
+        let mut game = game_with(vec![player, bystander(&mut rng)]);
+        let bullets = |game: &GameState| {
+            game.entities
+                .iter()
+                .filter(|entity| matches!(entity.kind, EntityKind::Bullet { .. }))
+                .count()
+        };
+
+        game.handle_event(
+            &key_event(CONTROL_SCHEMES[0].fire, true),
+            settings,
+            &mut EventLog::disabled(),
+        );
+        let mut fired = bullets(&game);
+        let mut firing_ticks = vec![];
+        for tick in 0..ticks {
+            run_ticks(&mut game, 1, settings, &mut rng);
+            if bullets(&game) > fired {
+                firing_ticks.push(tick);
+            }
+            fired = bullets(&game);
+        }
+        firing_ticks
+    }
+
+    #[test]
+    fn held_fire_repeats_at_cooldown_cadence() {
+        let settings = Settings {
+            auto_fire: true,
+            ..Settings::default()
+        };
+        let cooldown = settings.frames(AUTO_FIRE_COOLDOWN_SECONDS) as usize;
+        let firing_ticks = ticks_firing_while_held(&settings, cooldown * 4);
+        assert_eq!(firing_ticks.len(), 3, "{firing_ticks:?}");
+        for (earlier, later) in firing_ticks.iter().tuple_windows() {
+            assert_eq!(later - earlier, cooldown);
+        }
+    }
+
+    #[test]
+    fn held_fire_shoots_once_without_auto_fire() {
+        let settings = Settings::default();
+        assert_eq!(ticks_firing_while_held(&settings, 60), vec![]);
+    }
+
+    #[test]
+    fn overlapping_pair_collides_once_until_it_separates() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let position = Vector::new(400.0, 300.0);
+        let (a, b) = (player_at(position, 3), player_at(position, 3));
+        let b_id = b.id;
+        let mut game = game_with(vec![a, b, bystander(&mut rng)]);
+        let buffer = SharedBuffer::default();
+        let mut log = EventLog::new(Box::new(buffer.clone()));
+        let move_b = |game: &mut GameState, to: Vector| {
+            let b = index_of(&game.entities, b_id).unwrap();
+            game.entities[b].body.position = to;
+            game.entities[b].body.prev_position = to;
+        };
+
+        for _ in 0..10 {
+            game.update(bounds(), &settings, &mut rng, &mut log);
+        }
+        assert_eq!(buffer.lines_with("event=collision").len(), 1);
+
+        move_b(&mut game, Vector::new(600.0, 300.0));
+        game.update(bounds(), &settings, &mut rng, &mut log);
+        move_b(&mut game, position);
+        for _ in 0..10 {
+            game.update(bounds(), &settings, &mut rng, &mut log);
+        }
+        assert_eq!(buffer.lines_with("event=collision").len(), 2);
+    }
+
+    #[test]
+    fn split_is_across_bullet_travel_not_its_rotation() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        // Pointing north, as if fired from a turning ship, but travelling east
+        let mut bullet = bullet_at(Vector::new(390.0, 300.0));
+        let travel = Vector::new(4.0, 0.0);
+        bullet.body.velocity = travel;
+        let mut game = game_with(vec![asteroid, bullet]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let pieces = asteroids(&game);
+        assert_eq!(pieces.len(), 2);
+        for piece in pieces {
+            let velocity = piece.body.velocity;
+            assert!(velocity.length() > 0.5, "{velocity}");
+            assert!(velocity.dot(travel).abs() < 1e-4, "{velocity}");
+        }
+    }
+
+    #[test]
+    fn conserved_split_keeps_the_parents_area() {
+        let settings = Settings {
+            conserve_mass: true,
+            ..Settings::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let asteroid = asteroid_at(3, center, &mut rng);
+        let parent_area = sprite_area(&asteroid);
+        let mut game = game_with(vec![asteroid, bullet_at(center)]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let pieces = asteroids(&game);
+        assert_eq!(pieces.len(), 2);
+        let children_area = pieces.iter().map(|piece| sprite_area(piece)).sum::<Float>();
+        assert!(
+            (children_area - parent_area).abs() < parent_area * 1e-3,
+            "{children_area} vs {parent_area}"
+        );
+    }
+
+    #[test]
+    fn time_bonus_extends_the_clock() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let asteroid = asteroid_at(3, center, &mut rng);
+        let mut game = game_with(vec![asteroid, bullet_at(center)]);
+        game.time_left = Some(100);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert_eq!(
+            game.time_left,
+            Some(99 + settings.frames(TIME_BONUS_SECONDS))
+        );
+    }
+
+    #[test]
+    fn two_bullets_in_one_asteroid_split_it_once() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let asteroid = asteroid_at(2, center, &mut rng);
+        let nearer = bullet_at(center + Vector::new(-5.0, 0.0));
+        let farther = bullet_at(center + Vector::new(10.0, 0.0));
+        let farther_id = farther.id;
+        let mut game = game_with(vec![asteroid, farther, nearer]);
+        let buffer = SharedBuffer::default();
+        let mut log = EventLog::new(Box::new(buffer.clone()));
+
+        game.update(bounds(), &settings, &mut rng, &mut log);
+        assert_eq!(buffer.lines_with("event=split").len(), 1);
+        let pieces = asteroids(&game);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces
+            .iter()
+            .all(|piece| matches!(piece.kind, EntityKind::Asteroid { size: 1, .. })));
+        let bullets = game
+            .entities
+            .iter()
+            .filter(|entity| matches!(entity.kind, EntityKind::Bullet { .. }))
+            .map(|bullet| bullet.id)
+            .collect::<Vec<_>>();
+        assert_eq!(bullets, [farther_id]);
+    }
+
+    #[test]
+    fn asteroids_stay_put_when_a_player_respawns() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(200.0, 300.0), 3);
+        let player_id = player.id;
+        let field = [
+            asteroid_at(2, Vector::new(200.0, 300.0), &mut rng),
+            asteroid_at(3, Vector::new(600.0, 100.0), &mut rng),
+            asteroid_at(1, Vector::new(600.0, 500.0), &mut rng),
+        ];
+        let snapshot = |game: &GameState| {
+            let mut asteroids = asteroids(game)
+                .iter()
+                .map(|asteroid| (asteroid.id, asteroid.body.position))
+                .collect::<Vec<_>>();
+            asteroids.sort_by_key(|&(id, _)| id);
+            asteroids
+        };
+        let mut game = game_with([player].into_iter().chain(field).collect());
+        let before = snapshot(&game);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert_eq!(lives_of(&game, player_id), Some(2));
+        assert_eq!(snapshot(&game), before);
+    }
+
+    #[test]
+    fn split_pieces_keep_about_the_parents_rotation() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let mut asteroid = asteroid_at(3, center, &mut rng);
+        asteroid.body.rotation = 2.0;
+        let mut game = game_with(vec![asteroid, bullet_at(center)]);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let pieces = asteroids(&game);
+        assert_eq!(pieces.len(), 2);
+        for piece in pieces {
+            let rotation = piece.body.rotation;
+            assert!(
+                (rotation - 2.0).abs() <= SPLIT_ROTATION_JITTER,
+                "{rotation}"
+            );
+        }
+    }
+
+    #[test]
+    fn stable_order_keeps_entities_in_id_order() {
+        let settings = Settings {
+            stable_order: true,
+            ..Settings::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        // Created, and so numbered, left to right
+        let mut entities = (1..=5)
+            .map(|i| asteroid_at(1, Vector::new(100.0 * i as Float, 300.0), &mut rng))
+            .collect::<Vec<_>>();
+        entities.swap(0, 3);
+        entities.swap(1, 4);
+        let mut game = game_with(entities);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let positions = game
+            .entities
+            .iter()
+            .map(|entity| entity.body.position.x.round())
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [100.0, 200.0, 300.0, 400.0, 500.0]);
+    }
+
+    #[test]
+    fn render_leaves_the_game_as_it_was() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut game = GameState::new(&settings, bounds(), &mut rng);
+        run_ticks(&mut game, 30, &settings, &mut rng);
+        let snapshot = |game: &GameState| {
+            let entities = game
+                .entities
+                .iter()
+                .map(|entity| (entity.id, entity.body.position, entity.body.velocity))
+                .collect::<Vec<_>>();
+            (entities, game.score, game.wave, game.contacts.len())
+        };
+        let before = snapshot(&game);
+
+        // A software canvas, so this needs SDL but no display
+        let size = bounds().size();
+        let surface =
+            Surface::new(size.x as u32, size.y as u32, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = surface.into_canvas().unwrap();
+        let mut lines = LineRenderer::new(&mut canvas, &settings);
+        let overlays = Overlays {
+            debug: true,
+            velocities: true,
+            contacts: true,
+        };
+        game.render(
+            &mut lines,
+            &mut canvas,
+            bounds(),
+            &settings,
+            &Theme::default(),
+            overlays,
+        );
+        assert_eq!(snapshot(&game), before);
+    }
+
+    #[test]
+    fn losing_focus_pauses_until_it_is_regained() {
+        let window_event = |win_event| Event::Window {
+            timestamp: 0,
+            window_id: 0,
+            win_event,
+        };
+        let mut paused = None;
+        let mut auto_paused = false;
+
+        pause_while_unfocused(
+            &window_event(WindowEvent::FocusLost),
+            &mut paused,
+            &mut auto_paused,
+        );
+        assert!(paused.is_some() && auto_paused);
+        pause_while_unfocused(
+            &window_event(WindowEvent::FocusGained),
+            &mut paused,
+            &mut auto_paused,
+        );
+        assert!(paused.is_none() && !auto_paused);
+
+        // Paused by hand, the game stays paused when focus comes back
+        paused = Some(PauseMenu::default());
+        pause_while_unfocused(
+            &window_event(WindowEvent::FocusLost),
+            &mut paused,
+            &mut auto_paused,
+        );
+        assert!(!auto_paused);
+        pause_while_unfocused(
+            &window_event(WindowEvent::FocusGained),
+            &mut paused,
+            &mut auto_paused,
+        );
+        assert!(paused.is_some());
+    }
+
+    #[test]
+    fn clicking_spawns_an_asteroid_where_the_click_lands() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        // A high-DPI window half the size of its renderer's output, drawn at twice the scale
+        let click = (100, 50);
+        let window_size = (400, 300);
+        let output = (800, 600);
+        let bounds = play_area(output, 2.0, WrapAxes::BOTH);
+
+        let position = click_to_world(click, window_size, output, None, 2.0, bounds, false);
+        assert_eq!(position, Vector::new(100.0, 50.0));
+        let inverted = click_to_world(click, window_size, output, None, 2.0, bounds, true);
+        assert_eq!(inverted, Vector::new(100.0, 250.0));
+
+        let asteroid = debug_asteroid(2, position, &settings, &mut rng);
+        assert_eq!(asteroid.body.position, position);
+        assert_eq!(asteroid.body.velocity, Vector::ZERO);
+        assert!(matches!(
+            asteroid.kind,
+            EntityKind::Asteroid { size: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn bouncy_debris_reverses_off_asteroids() {
+        let debris_velocity_after_a_tick = |bouncy_debris| {
+            let settings = Settings {
+                bouncy_debris,
+                ..Settings::default()
+            };
+            let mut rng = StdRng::seed_from_u64(0);
+            let asteroid = asteroid_at(3, Vector::new(400.0, 300.0), &mut rng);
+            let position = Vector::new(385.0, 300.0);
+            let body = Body {
+                position,
+                prev_position: position,
+                velocity: Vector::new(2.0, 0.0),
+                ..Default::default()
+            };
+            let debris = new_debris(body, DEBRIS_TTL, &mut rng);
+            let debris_id = debris.id;
+            let mut game = game_with(vec![asteroid, debris]);
+            run_ticks(&mut game, 1, &settings, &mut rng);
+            let debris = &game.entities[index_of(&game.entities, debris_id).unwrap()];
+            debris.body.velocity
+        };
+        // Slowed a little by drag either way
+        let passing = debris_velocity_after_a_tick(false);
+        assert!(passing.x > 1.5 && passing.y == 0.0, "{passing}");
+        let bounced = debris_velocity_after_a_tick(true);
+        assert!(bounced.x < -1.5 && bounced.y == 0.0, "{bounced}");
+    }
+
+    #[test]
+    fn tough_asteroid_splits_only_on_its_last_hit() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let center = Vector::new(400.0, 300.0);
+        let mut asteroid = asteroid_at(3, center, &mut rng);
+        let EntityKind::Asteroid { hits_remaining, .. } = &mut asteroid.kind else {
+            unreachable!()
+        };
+        *hits_remaining = 3;
+        let asteroid_id = asteroid.id;
+        let mut game = game_with(vec![asteroid]);
+
+        for hits_left in [2, 1] {
+            game.entities.push(bullet_at(center));
+            run_ticks(&mut game, 1, &settings, &mut rng);
+            let pieces = asteroids(&game);
+            assert_eq!(pieces.len(), 1);
+            assert_eq!(pieces[0].id, asteroid_id);
+            assert!(matches!(
+                pieces[0].kind,
+                EntityKind::Asteroid { hits_remaining, .. } if hits_remaining == hits_left
+            ));
+        }
+        game.entities.push(bullet_at(center));
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        let pieces = asteroids(&game);
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces
+            .iter()
+            .all(|piece| matches!(piece.kind, EntityKind::Asteroid { size: 2, .. })));
+    }
+
+    /// An entity at `position` whose only bounding triangle is `triangle`, relative to it.
+    fn triangle_at(position: Vector, triangle: [(Float, Float); 3]) -> Entity {
+        Entity::builder(EntityKind::Debris {
+            ttl: 1,
+            lifetime: 1,
+        })
+        .body(ship_body(position, 0.0))
+        .bounding(Bounding::Triangles {
+            triangles: Either::Right([triangle.map(|(x, y)| Vector::new(x, y))].into()),
+        })
+        .build()
+    }
+
+    #[test]
+    fn contact_point_is_inside_both_triangles() {
+        let center = Vector::new(400.0, 300.0);
+        let pairs = [
+            // One corner poking into the other
+            (
+                triangle_at(center, [(0.0, 0.0), (40.0, 0.0), (0.0, 40.0)]),
+                triangle_at(
+                    center + Vector::new(30.0, 30.0),
+                    [(0.0, 0.0), (-20.0, 0.0), (0.0, -20.0)],
+                ),
+            ),
+            // A star, where neither has a corner inside the other
+            (
+                triangle_at(center, [(0.0, -20.0), (17.0, 10.0), (-17.0, 10.0)]),
+                triangle_at(center, [(0.0, 20.0), (-17.0, -10.0), (17.0, -10.0)]),
+            ),
+        ];
+        for (a, b) in &pairs {
+            let a_triangle = a.bounding_triangles().next().unwrap();
+            let b_triangle = b.bounding_triangles().next().unwrap();
+            for contact in [a.collision(b, bounds()), b.collision(a, bounds())] {
+                let point = contact.expect("the triangles overlap").point;
+                assert!(geometry::point_in_triangle(point, a_triangle), "{point}");
+                assert!(geometry::point_in_triangle(point, b_triangle), "{point}");
+            }
+        }
+    }
+
+    #[test]
+    fn collision_separates_along_the_shallowest_edge() {
+        let center = Vector::new(400.0, 300.0);
+        let corner = [(0.0, 0.0), (40.0, 0.0), (0.0, 40.0)];
+        let a = triangle_at(center, corner);
+        // Overlapping 5 across, but less than that across `a`'s slanted edge
+        let b = triangle_at(center + Vector::new(35.0, 0.0), corner);
+        let diagonal = Vector::new(1.0, 1.0).normalize();
+        let expected_depth = 5.0 / (2.0 as Float).sqrt();
+
+        let contact = a.collision(&b, bounds()).unwrap();
+        assert!(contact.normal.distance(diagonal) < 1e-4, "{contact:?}");
+        assert!((contact.depth - expected_depth).abs() < 1e-4, "{contact:?}");
+        let contact = b.collision(&a, bounds()).unwrap();
+        assert!(contact.normal.distance(-diagonal) < 1e-4, "{contact:?}");
+        assert!((contact.depth - expected_depth).abs() < 1e-4, "{contact:?}");
+
+        // Moved along the normal by the depth, and a little more, they no longer touch
+        let moved = triangle_at(
+            center + Vector::new(35.0, 0.0) + diagonal * (expected_depth + 0.01),
+            corner,
+        );
+        assert!(a.collision(&moved, bounds()).is_none());
+    }
+
+    #[test]
+    fn step_hook_moves_an_entity_as_it_dictates() {
+        /// Bobs up and down, a sine wave of height 10 every 20 steps.
+        fn bob(body: &mut Body, age: u64) {
+            body.position.y = 300.0 + 10.0 * (age as Float * consts::TAU / 20.0).sin();
+        }
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+        asteroid.body.velocity = Vector::new(1.0, 0.0);
+        asteroid.on_step = Some(bob);
+
+        for age in 0..40 {
+            asteroid.step(bounds(), &settings);
+            let expected = Vector::new(
+                401.0 + age as Float,
+                300.0 + 10.0 * (age as Float * consts::TAU / 20.0).sin(),
+            );
+            assert!(
+                asteroid.body.position.distance(expected) < 1e-3,
+                "step {age}: {}",
+                asteroid.body.position
+            );
+        }
+    }
+
+    #[test]
+    fn skin_shapes_replace_generated_asteroids() {
+        let skin = skin::Skin::parse(
+            "# A square for the middle size\n\
+             shape 2 -20,-20 20,-20 20,20 -20,20\n\
+             color 2 ff8000\n",
+        )
+        .unwrap();
+        let square = [(-20.0, -20.0), (20.0, -20.0), (20.0, 20.0), (-20.0, 20.0)]
+            .map(|(x, y)| Vector::new(x, y));
+        assert_eq!(skin.colors[1], Some(Color::RGB(0xff, 0x80, 0x00)));
+        let mut shapes = AsteroidShapeTable::default();
+        shapes.set_templates(skin.shapes);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let body = ship_body(Vector::new(400.0, 300.0), 0.0);
+        let skinned = new_asteroid(2, body, &shapes, &mut rng);
+        assert_eq!(&skinned.sprite_verts.unwrap().verts[..], square);
+        // Sizes the skin has no shapes for are generated as usual
+        let generated = new_asteroid(1, body, &shapes, &mut rng);
+        assert_eq!(
+            generated.sprite_verts.unwrap().verts.len(),
+            shapes.shape(1).vert_count
+        );
+    }
+
+    #[test]
+    fn stepping_in_an_empty_play_area_keeps_positions_finite() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        for wrap in ["both", "horizontal", "none"] {
+            let empty = Bounds::new(UVec2::ZERO, WrapAxes::preset(wrap).unwrap());
+            let mut asteroid = asteroid_at(2, Vector::new(400.0, 300.0), &mut rng);
+            asteroid.body.velocity = Vector::new(-3.0, 2.0);
+            let mut player = player_at(Vector::new(100.0, 100.0), 3);
+            player.body.velocity = Vector::new(1.0, -1.0);
+            for mut entity in [asteroid, player] {
+                for _ in 0..10 {
+                    entity.step(empty, &settings);
+                }
+                let Body {
+                    position,
+                    prev_position,
+                    velocity,
+                    ..
+                } = entity.body;
+                assert!(
+                    position.is_finite() && prev_position.is_finite() && velocity.is_finite(),
+                    "{wrap}: {position} {velocity}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn removals_carry_why_they_happened() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut expiring = bullet_at(Vector::new(100.0, 100.0));
+        let EntityKind::Bullet { ttl, .. } = &mut expiring.kind else {
+            unreachable!()
+        };
+        *ttl = 0;
+        let expiring_id = expiring.id;
+        let target = Vector::new(400.0, 300.0);
+        let asteroid = asteroid_at(1, target, &mut rng);
+        let asteroid_id = asteroid.id;
+        let hitting = bullet_at(target);
+        let hitting_id = hitting.id;
+        let mut game = game_with(vec![expiring, asteroid, hitting, bystander(&mut rng)]);
+        let buffer = SharedBuffer::default();
+        let mut log = EventLog::new(Box::new(buffer.clone()));
+
+        game.update(bounds(), &settings, &mut rng, &mut log);
+        let reason_of = |id: u64| {
+            let removals = buffer.lines_with(&format!("event=remove id={id} "));
+            assert_eq!(removals.len(), 1, "{removals:?}");
+            removals[0].rsplit_once("reason=").unwrap().1.to_owned()
+        };
+        assert_eq!(reason_of(expiring_id), "expired");
+        assert_eq!(reason_of(asteroid_id), "destroyed");
+        assert_eq!(reason_of(hitting_id), "destroyed");
+
+        // Only a bullet that hit something sparks
+        let bullet = bullet_at(target);
+        assert!(death_effects(&bullet, RemoveReason::Expired, &mut rng).is_empty());
+        assert!(!death_effects(&bullet, RemoveReason::Destroyed, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn debris_slows_with_its_own_drag() {
+        let position = Vector::new(400.0, 300.0);
+        let body = Body {
+            position,
+            prev_position: position,
+            velocity: Vector::new(3.0, 0.0),
+            ..Default::default()
+        };
+        let speeds_over_lifetime = |debris_drag| {
+            let settings = Settings {
+                debris_drag,
+                ..Settings::default()
+            };
+            let mut debris = new_debris(body, DEBRIS_TTL, &mut StdRng::seed_from_u64(0));
+            (0..DEBRIS_TTL)
+                .map(|_| {
+                    debris.step(bounds(), &settings);
+                    debris.body.velocity.length()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let speeds = speeds_over_lifetime(0.03);
+        assert!(speeds.windows(2).all(|pair| pair[1] < pair[0]));
+        let expected = 3.0 * (0.97 as Float).powi(DEBRIS_TTL as i32);
+        assert!((speeds.last().unwrap() - expected).abs() < 1e-3);
+        // Ships' drag doesn't apply to debris
+        assert!(speeds_over_lifetime(0.0).iter().all(|&speed| speed == 3.0));
+    }
+
+    #[test]
+    fn protected_asteroid_collides_once_protection_runs_out() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let position = Vector::new(400.0, 300.0);
+        let player = player_at(position, 3);
+        let player_id = player.id;
+        let mut asteroid = asteroid_at(2, position, &mut rng);
+        let EntityKind::Asteroid {
+            spawn_protection, ..
+        } = &mut asteroid.kind
+        else {
+            unreachable!()
+        };
+        *spawn_protection = 3;
+
+        let mut stepped = asteroid.clone();
+        for _ in 0..3 {
+            assert!(!stepped.collides_with(&player, &settings));
+            assert!(!player.collides_with(&stepped, &settings));
+            stepped.step(bounds(), &settings);
+        }
+        assert!(stepped.collides_with(&player, &settings));
+
+        // Sitting on the player the whole time, it only costs a life once it runs out
+        let mut game = game_with(vec![player, asteroid]);
+        run_ticks(&mut game, 2, &settings, &mut rng);
+        assert_eq!(lives_of(&game, player_id), Some(3));
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert_eq!(lives_of(&game, player_id), Some(2));
+    }
+
+    #[test]
+    fn bullets_take_their_shooters_color() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut fired_colors = |players| {
+            let settings = Settings {
+                players,
+                ..Settings::default()
+            };
+            let mut players = initial_entities(&settings, bounds(), &mut rng);
+            players.retain(|entity| matches!(entity.kind, EntityKind::Player { .. }));
+            let last = players.last().unwrap();
+            let (last_id, last_color) = (last.id, last.color);
+            let mut game = game_with(players);
+            game.handle_event(
+                &key_event(CONTROL_SCHEMES[settings.players - 1].fire, true),
+                &settings,
+                &mut EventLog::disabled(),
+            );
+            let bullets = game
+                .entities
+                .iter()
+                .filter_map(|entity| match entity.kind {
+                    EntityKind::Bullet { owner, .. } => Some((owner, entity.color)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            (bullets, last_id, last_color)
+        };
+
+        let (bullets, player_2, color) = fired_colors(2);
+        assert_eq!(color, Some(PLAYER_COLORS[1]));
+        assert_eq!(bullets, [(player_2, color)]);
+        // A lone player's bullets are drawn in the theme's bullet color
+        let (bullets, player_1, _) = fired_colors(1);
+        assert_eq!(bullets, [(player_1, None)]);
+    }
+
+    #[test]
+    fn hyperspace_within_its_cooldown_is_ignored() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let start = Vector::new(400.0, 300.0);
+        let player = player_at(start, 3);
+        let player_id = player.id;
+        let mut game = game_with(vec![player, bystander(&mut rng)]);
+        let position = |game: &GameState| {
+            game.entities[index_of(&game.entities, player_id).unwrap()]
+                .body
+                .position
+        };
+        let jump = |game: &mut GameState, rng: &mut StdRng| {
+            game.handle_event(
+                &key_event(CONTROL_SCHEMES[0].hyperspace, true),
+                &settings,
+                &mut EventLog::disabled(),
+            );
+            run_ticks(game, 1, &settings, rng);
+        };
+
+        jump(&mut game, &mut rng);
+        let landed = position(&game);
+        assert_ne!(landed, start);
+        jump(&mut game, &mut rng);
+        assert_eq!(position(&game), landed);
+
+        let cooldown = settings.frames(HYPERSPACE_COOLDOWN_SECONDS) as usize;
+        run_ticks(&mut game, cooldown, &settings, &mut rng);
+        jump(&mut game, &mut rng);
+        assert_ne!(position(&game), landed);
+    }
+
+    #[test]
+    fn window_icon_is_drawn_at_icon_size() {
+        // Drawn into a plain surface, so this needs SDL but no display
+        let icon = window_icon().unwrap();
+        assert_eq!(icon.size(), (ICON_SIZE, ICON_SIZE));
+    }
+
+    #[test]
+    fn render_scale_keeps_the_play_area_in_world_units() {
+        // A 800 by 600 window on a display with twice the pixels, drawn at 1.5 times that again
+        let play = play_area((2400, 1800), 2.0 * 1.5, WrapAxes::BOTH);
+        assert_eq!(play.size(), Vector::new(800.0, 600.0));
+        assert_eq!(
+            play_area((800, 600), 1.0, WrapAxes::BOTH).size(),
+            play.size()
+        );
+    }
+
+    #[test]
+    fn players_are_not_hit_by_their_own_bullets() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let position = Vector::new(400.0, 300.0);
+        let player = player_at(position, 3);
+        let player_id = player.id;
+        let own = new_bullet(ship_body(position, 0.0), player_id, None, BULLET_TTL);
+        let other = bullet_at(position);
+        assert!(!player.collides_with(&own, &settings));
+        assert!(!own.collides_with(&player, &settings));
+        assert!(player.collides_with(&other, &settings));
+
+        // Nor does flying through it in the game cost a life
+        let own_id = own.id;
+        let mut game = game_with(vec![player, own, bystander(&mut rng)]);
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert_eq!(lives_of(&game, player_id), Some(3));
+        assert!(index_of(&game.entities, own_id).is_some());
+    }
+
+    #[test]
+    fn running_out_of_time_ends_the_game() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let player = player_at(Vector::new(100.0, 100.0), 3);
+        let mut asteroid = asteroid_at(3, Vector::new(400.0, 300.0), &mut rng);
+        asteroid.body.velocity = Vector::new(1.0, 0.0);
+        let asteroid_id = asteroid.id;
+        let mut game = game_with(vec![player, asteroid]);
+        game.time_left = Some(2);
+
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert!(!game.is_over());
+        run_ticks(&mut game, 1, &settings, &mut rng);
+        assert!(game.is_over());
+        assert!(!game
+            .entities
+            .iter()
+            .any(|entity| matches!(entity.kind, EntityKind::Player { .. })));
+
+        // Nothing moves once the game is over
+        let position = |game: &GameState| {
+            game.entities[index_of(&game.entities, asteroid_id).unwrap()]
+                .body
+                .position
+        };
+        let stopped_at = position(&game);
+        run_ticks(&mut game, 10, &settings, &mut rng);
+        assert_eq!(position(&game), stopped_at);
+        assert_eq!(game.time_left, Some(0));
+    }
+}
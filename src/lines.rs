@@ -0,0 +1,348 @@
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_int};
+
+use sdl2::pixels::Color;
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
+
+use crate::as_point::AsPoint;
+use crate::precision::{Float, Vector};
+use crate::settings::Settings;
+
+/// Layout-compatible with `SDL_Vertex`, which `sdl2` 0.35 doesn't bind.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: [f32; 2],
+    /// RGBA
+    pub color: [u8; 4],
+    pub tex_coord: [f32; 2],
+}
+
+/// The type of `SDL_RenderGeometry`, which `sdl2` 0.35 doesn't bind either.
+type RenderGeometry = unsafe extern "C" fn(
+    renderer: *mut sdl2::sys::SDL_Renderer,
+    texture: *mut sdl2::sys::SDL_Texture,
+    vertices: *const Vertex,
+    num_vertices: c_int,
+    indices: *const c_int,
+    num_indices: c_int,
+) -> c_int;
+
+/// Looks up `SDL_RenderGeometry` in the SDL the game is running with, rather than linking against
+/// it, so the game still starts with SDL older than 2.0.18, which lacks it.
+fn load_render_geometry() -> Option<RenderGeometry> {
+    // The program's own handle reaches SDL's exports too, except on Windows, where its DLL must be
+    // named
+    let library: *const c_char = if cfg!(windows) {
+        c"SDL2.dll".as_ptr()
+    } else {
+        std::ptr::null()
+    };
+    // SAFETY: `library` is null or a C string. If found, the function has the signature of
+    // `RenderGeometry`, and stays loaded since the handle is never unloaded.
+    unsafe {
+        let handle = sdl2::sys::SDL_LoadObject(library);
+        if handle.is_null() {
+            return None;
+        }
+        let function: *mut c_void =
+            sdl2::sys::SDL_LoadFunction(handle, c"SDL_RenderGeometry".as_ptr());
+        if function.is_null() {
+            return None;
+        }
+        Some(std::mem::transmute::<*mut c_void, RenderGeometry>(function))
+    }
+}
+
+/// Anti-aliased lines waiting for `LineRenderer::flush`, and what draws them.
+struct Geometry {
+    render: RenderGeometry,
+    vertices: Vec<Vertex>,
+    indices: Vec<c_int>,
+}
+
+/// Half the width of the solid core of an anti-aliased line, in pixels
+const CORE_HALF_WIDTH: Float = 0.5;
+/// Width of the fade from opaque to transparent at either side of an anti-aliased line, in pixels
+const FEATHER_WIDTH: Float = 1.0;
+
+/// Builds the geometry for one anti-aliased segment: a solid core quad with a quad on either side
+/// fading to transparent, all `scale` times as wide as at the usual scale.
+///
+/// The vertices are, for each endpoint in turn, from one side of the line to the other.
+pub fn segment_quads(
+    p1: Vector,
+    p2: Vector,
+    color: Color,
+    scale: Float,
+) -> ([Vertex; 8], [c_int; 18]) {
+    let normal = (p2 - p1).normalize_or_zero().perp() * scale;
+    let offsets = [
+        (CORE_HALF_WIDTH + FEATHER_WIDTH, 0),
+        (CORE_HALF_WIDTH, color.a),
+        (-CORE_HALF_WIDTH, color.a),
+        (-CORE_HALF_WIDTH - FEATHER_WIDTH, 0),
+    ];
+    let vertex = |point: Vector, (offset, alpha): (Float, u8)| {
+        let position = point + normal * offset;
+        // `Float` is already `f32` with the `f32` feature
+        #[allow(clippy::unnecessary_cast)]
+        Vertex {
+            position: [position.x as f32, position.y as f32],
+            color: [color.r, color.g, color.b, alpha],
+            tex_coord: [0.0, 0.0],
+        }
+    };
+    let vertices = [
+        vertex(p1, offsets[0]),
+        vertex(p1, offsets[1]),
+        vertex(p1, offsets[2]),
+        vertex(p1, offsets[3]),
+        vertex(p2, offsets[0]),
+        vertex(p2, offsets[1]),
+        vertex(p2, offsets[2]),
+        vertex(p2, offsets[3]),
+    ];
+    let mut indices = [0; 18];
+    for strip in 0..3 {
+        let (a0, a1, b0, b1) = (strip, strip + 1, strip + 4, strip + 5);
+        indices[strip as usize * 6..][..6].copy_from_slice(&[a0, a1, b0, a1, b1, b0]);
+    }
+    (vertices, indices)
+}
+
+/// Draws line segments, either directly with `draw_line`,
+/// or batched as anti-aliased geometry drawn by `flush`.
+///
+/// Either way, the alpha of each line's color is honored.
+pub struct LineRenderer {
+    round_points: bool,
+    /// The height of the area being mirrored top to bottom, if any
+    mirror_height: Option<Float>,
+    /// Pixels drawn across for each unit of the points given to `line`, and how many times
+    /// thicker than a pixel lines are
+    scale: Float,
+    /// `None` if not anti-aliasing
+    geometry: Option<Geometry>,
+}
+
+impl LineRenderer {
+    pub fn new<T: RenderTarget>(canvas: &mut Canvas<T>, settings: &Settings) -> LineRenderer {
+        let render = settings.antialias.then(load_render_geometry).flatten();
+        if settings.antialias && render.is_none() {
+            let version = sdl2::version::version();
+            eprintln!("Anti-aliasing needs SDL 2.0.18 or later, but this is SDL {version}");
+        }
+        // For the alpha of aliased lines too, not just the feathered edges of anti-aliased ones
+        canvas.set_blend_mode(BlendMode::Blend);
+        LineRenderer {
+            round_points: settings.round_points,
+            mirror_height: None,
+            scale: 1.0,
+            geometry: render.map(|render| Geometry {
+                render,
+                vertices: vec![],
+                indices: vec![],
+            }),
+        }
+    }
+
+    /// Mirrors everything drawn from now on top to bottom within an area `height` tall,
+    /// or stops mirroring if `None`.
+    pub fn set_mirror(&mut self, height: Option<Float>) {
+        self.mirror_height = height;
+    }
+
+    /// Draws everything from now on `scale` times larger, with lines as much thicker.
+    pub fn set_scale(&mut self, scale: Float) {
+        self.scale = scale;
+    }
+
+    pub fn scale(&self) -> Float {
+        self.scale
+    }
+
+    /// Converts a point given to `line` to the pixels of whatever is being drawn to.
+    pub fn to_backbuffer(&self, point: Vector) -> Vector {
+        let point = match self.mirror_height {
+            Some(height) => Vector::new(point.x, height - point.y),
+            None => point,
+        };
+        point * self.scale
+    }
+
+    pub fn line<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        p1: Vector,
+        p2: Vector,
+        color: Color,
+    ) {
+        let (p1, p2) = (self.to_backbuffer(p1), self.to_backbuffer(p2));
+        match &mut self.geometry {
+            Some(geometry) => {
+                let (new_vertices, new_indices) = segment_quads(p1, p2, color, self.scale);
+                let base = geometry.vertices.len() as c_int;
+                geometry.vertices.extend(new_vertices);
+                geometry
+                    .indices
+                    .extend(new_indices.map(|index| base + index));
+            }
+            None => {
+                canvas.set_draw_color(color);
+                // Thickened with parallel strokes a pixel apart, across whichever axis the line
+                // runs along least
+                let strokes = self.scale.round().max(1.0) as i32;
+                let delta = p2 - p1;
+                let across = if delta.x.abs() >= delta.y.abs() {
+                    Vector::Y
+                } else {
+                    Vector::X
+                };
+                for stroke in 0..strokes {
+                    let offset = across * (stroke - strokes / 2) as Float;
+                    canvas
+                        .draw_line(self.to_point(p1 + offset), self.to_point(p2 + offset))
+                        .ok();
+                }
+            }
+        }
+    }
+
+    /// Draws any batched geometry.
+    pub fn flush<T: RenderTarget>(&mut self, canvas: &mut Canvas<T>) {
+        let Some(Geometry {
+            render,
+            vertices,
+            indices,
+        }) = &mut self.geometry
+        else {
+            return;
+        };
+        // SAFETY: the renderer is live for as long as the canvas, and the buffers are valid for
+        // the given lengths. Every index is in bounds, since they are offsets into `vertices`.
+        let result = unsafe {
+            render(
+                canvas.raw(),
+                std::ptr::null_mut(),
+                vertices.as_ptr(),
+                vertices.len() as c_int,
+                indices.as_ptr(),
+                indices.len() as c_int,
+            )
+        };
+        vertices.clear();
+        indices.clear();
+        if result != 0 {
+            eprintln!(
+                "Failed to render anti-aliased lines, falling back to aliased lines: {}",
+                sdl2::get_error()
+            );
+            self.geometry = None;
+        }
+    }
+
+    fn to_point(&self, point: Vector) -> sdl2::rect::Point {
+        if self.round_points {
+            point.to_point_rounded()
+        } else {
+            point.as_point()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::PixelFormatEnum;
+    use sdl2::surface::Surface;
+
+    use super::*;
+
+    #[test]
+    fn segment_quads_of_horizontal_segment() {
+        let color = Color::RGBA(10, 20, 30, 200);
+        let (vertices, indices) =
+            segment_quads(Vector::new(0.0, 0.0), Vector::new(10.0, 0.0), color, 1.0);
+
+        // Across the line from one side to the other, at the start then at the end
+        let ys = [1.5, 0.5, -0.5, -1.5];
+        let alphas = [0, 200, 200, 0];
+        for (i, vertex) in vertices.iter().enumerate() {
+            let x = if i < 4 { 0.0 } else { 10.0 };
+            assert_eq!(vertex.position, [x, ys[i % 4]], "vertex {i}");
+            assert_eq!(vertex.color, [10, 20, 30, alphas[i % 4]], "vertex {i}");
+        }
+        // Two triangles for each of the three strips
+        assert_eq!(
+            indices,
+            [0, 1, 4, 1, 5, 4, 1, 2, 5, 2, 6, 5, 2, 3, 6, 3, 7, 6]
+        );
+    }
+
+    #[test]
+    fn segment_quads_widen_with_scale() {
+        let (vertices, _) = segment_quads(
+            Vector::new(0.0, 0.0),
+            Vector::new(0.0, 10.0),
+            Color::WHITE,
+            2.0,
+        );
+        let width = vertices[0].position[0] - vertices[3].position[0];
+        assert_eq!(width.abs(), 6.0);
+    }
+
+    /// A renderer that draws with `draw_line`, which needs no canvas until it draws.
+    fn renderer() -> LineRenderer {
+        LineRenderer {
+            round_points: false,
+            mirror_height: None,
+            scale: 1.0,
+            geometry: None,
+        }
+    }
+
+    #[test]
+    fn inverted_heading_lands_at_mirrored_point() {
+        let mut lines = renderer();
+        let ship = Vector::new(400.0, 100.0);
+        let nose = ship + crate::forward(0.5) * 20.0;
+        assert_eq!(lines.to_backbuffer(nose), nose);
+
+        lines.set_mirror(Some(600.0));
+        assert_eq!(lines.to_backbuffer(ship), Vector::new(400.0, 500.0));
+        assert_eq!(
+            lines.to_backbuffer(nose),
+            Vector::new(nose.x, 600.0 - nose.y)
+        );
+    }
+
+    #[test]
+    fn render_scale_maps_world_points_to_backbuffer_pixels() {
+        let mut lines = renderer();
+        lines.set_scale(2.0);
+        assert_eq!(
+            lines.to_backbuffer(Vector::new(10.0, 20.0)),
+            Vector::new(20.0, 40.0)
+        );
+        assert_eq!(
+            lines.to_backbuffer(Vector::new(800.0, 600.0)),
+            Vector::new(1600.0, 1200.0)
+        );
+        // Mirrored within the play area first, then scaled
+        lines.set_mirror(Some(600.0));
+        assert_eq!(
+            lines.to_backbuffer(Vector::new(10.0, 20.0)),
+            Vector::new(20.0, 1160.0)
+        );
+    }
+
+    #[test]
+    fn new_enables_alpha_blending() {
+        // A software canvas, so this needs SDL but no display
+        let surface = Surface::new(8, 8, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = surface.into_canvas().unwrap();
+        canvas.set_blend_mode(BlendMode::None);
+        LineRenderer::new(&mut canvas, &Settings::default());
+        assert_eq!(canvas.blend_mode(), BlendMode::Blend);
+    }
+}
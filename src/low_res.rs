@@ -0,0 +1,120 @@
+//! Drawing at a fixed low resolution, scaled up to fill as much of the window as a whole number of
+//! times will, for a blocky look.
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use crate::precision::{Float, Vector};
+use crate::trails::set_render_target;
+
+/// Returns how many times larger than `internal` to draw it in a window of size `window`: the
+/// largest whole number that fits on both axes, but at least 1.
+pub fn integer_scale(window: (u32, u32), internal: (u32, u32)) -> u32 {
+    (window.0 / internal.0).min(window.1 / internal.1).max(1)
+}
+
+/// Returns where to draw `internal` in a window of size `window`, scaled up by `integer_scale` and
+/// centered.
+pub fn scaled_rect(window: (u32, u32), internal: (u32, u32)) -> Rect {
+    let scale = integer_scale(window, internal);
+    let (width, height) = (internal.0 * scale, internal.1 * scale);
+    Rect::new(
+        (window.0 as i32 - width as i32) / 2,
+        (window.1 as i32 - height as i32) / 2,
+        width,
+        height,
+    )
+}
+
+/// A frame drawn at a fixed resolution, whatever the size of the window.
+pub struct LowRes<'a> {
+    texture: Texture<'a>,
+    /// The resolution the frame is laid out at
+    size: (u32, u32),
+    /// The pixels actually drawn into, which may be a multiple of `size` for more detail
+    texture_size: (u32, u32),
+}
+
+impl<'a> LowRes<'a> {
+    /// A frame of `size`, drawn into a texture `scale` times as large.
+    pub fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        size: (u32, u32),
+        scale: Float,
+    ) -> Result<LowRes<'a>, String> {
+        // Scale up without blurring, so each pixel stays a sharp square
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "nearest");
+        let texture_size = (
+            ((size.0 as Float * scale).round() as u32).max(1),
+            ((size.1 as Float * scale).round() as u32).max(1),
+        );
+        let texture = texture_creator
+            .create_texture_target(None, texture_size.0, texture_size.1)
+            .map_err(|e| e.to_string())?;
+        Ok(LowRes {
+            texture,
+            size,
+            texture_size,
+        })
+    }
+
+    pub fn texture(&self) -> &Texture<'a> {
+        &self.texture
+    }
+
+    pub fn texture_size(&self) -> (u32, u32) {
+        self.texture_size
+    }
+
+    /// Redirects drawing into the low-resolution frame.
+    pub fn begin(&self, canvas: &mut WindowCanvas) -> Result<(), String> {
+        set_render_target(canvas, Some(&self.texture))
+    }
+
+    /// Draws the frame to the window, scaled up and centered with `background` around it. The
+    /// window is drawn to directly again afterwards.
+    pub fn end(&self, canvas: &mut WindowCanvas, background: Color) -> Result<(), String> {
+        set_render_target(canvas, None)?;
+        let output = canvas.output_size()?;
+        canvas.set_draw_color(background);
+        canvas.clear();
+        canvas.copy(&self.texture, None, scaled_rect(output, self.size))
+    }
+
+    /// Converts a point in the pixels of a window of size `output` to the low-resolution frame's,
+    /// at its layout resolution.
+    pub fn to_internal(&self, point: Vector, output: (u32, u32)) -> Vector {
+        let rect = scaled_rect(output, self.size);
+        let scale = integer_scale(output, self.size) as Float;
+        (point - Vector::new(rect.x() as Float, rect.y() as Float)) / scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_scale_is_the_largest_that_fits_both_ways() {
+        let internal = (320, 240);
+        assert_eq!(integer_scale((320, 240), internal), 1);
+        assert_eq!(integer_scale((640, 480), internal), 2);
+        assert_eq!(integer_scale((1920, 1080), internal), 4);
+        // Limited by the narrower axis
+        assert_eq!(integer_scale((2000, 500), internal), 2);
+        assert_eq!(integer_scale((700, 1000), internal), 2);
+        // Never below 1, even if that doesn't fit
+        assert_eq!(integer_scale((100, 100), internal), 1);
+    }
+
+    #[test]
+    fn scaled_rect_is_centered() {
+        let rect = scaled_rect((1920, 1080), (320, 240));
+        assert_eq!(rect, Rect::new(320, 60, 1280, 960));
+        // Overhanging the window evenly when too small
+        let rect = scaled_rect((100, 100), (320, 240));
+        assert_eq!(rect, Rect::new(-110, -70, 320, 240));
+    }
+}
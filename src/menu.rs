@@ -0,0 +1,131 @@
+use sdl2::keyboard::Keycode;
+use sdl2::render::WindowCanvas;
+
+use crate::bounds::Bounds;
+use crate::difficulty::Difficulty;
+use crate::lines::LineRenderer;
+use crate::precision::{Float, Vector};
+use crate::shade;
+use crate::text::{draw_text, text_width, GLYPH_HEIGHT};
+use crate::theme::Theme;
+
+/// Pixels per grid unit of the menu text
+const TEXT_SCALE: Float = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseOption {
+    Resume,
+    /// Start a new game from the first wave.
+    Restart,
+    /// Switch to the next difficulty preset, from the next wave on.
+    Difficulty,
+    Quit,
+}
+
+impl PauseOption {
+    /// In the order they are listed in the menu.
+    const ALL: [PauseOption; 4] = [
+        PauseOption::Resume,
+        PauseOption::Restart,
+        PauseOption::Difficulty,
+        PauseOption::Quit,
+    ];
+
+    fn label(self, difficulty: &Difficulty) -> String {
+        match self {
+            PauseOption::Resume => "Resume".to_owned(),
+            PauseOption::Restart => "Restart".to_owned(),
+            PauseOption::Difficulty => format!("Difficulty: {}", difficulty.name),
+            PauseOption::Quit => "Quit".to_owned(),
+        }
+    }
+}
+
+/// The menu shown while the game is paused.
+#[derive(Debug, Default)]
+pub struct PauseMenu {
+    /// Index into `PauseOption::ALL`
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn selected(&self) -> PauseOption {
+        PauseOption::ALL[self.selected]
+    }
+
+    /// Moves the selection with the arrow keys, wrapping around at either end.
+    ///
+    /// Returns the selected option if `keycode` chooses it.
+    pub fn handle_key(&mut self, keycode: Keycode) -> Option<PauseOption> {
+        let count = PauseOption::ALL.len();
+        match keycode {
+            Keycode::Up => self.selected = (self.selected + count - 1) % count,
+            Keycode::Down => self.selected = (self.selected + 1) % count,
+            Keycode::Return | Keycode::KpEnter => return Some(self.selected()),
+            _ => {}
+        }
+        None
+    }
+
+    /// Draws the options centered on the screen, marking the selected one.
+    pub fn draw(
+        &self,
+        lines: &mut LineRenderer,
+        canvas: &mut WindowCanvas,
+        bounds: Bounds,
+        theme: &Theme,
+        difficulty: &Difficulty,
+    ) {
+        let line_height = (GLYPH_HEIGHT + 4.0) * TEXT_SCALE;
+        let top = (bounds.size().y - line_height * PauseOption::ALL.len() as Float) / 2.0;
+        for (i, option) in PauseOption::ALL.into_iter().enumerate() {
+            let (text, color) = if i == self.selected {
+                (format!("> {}", option.label(difficulty)), theme.hud)
+            } else {
+                (option.label(difficulty), shade(theme.hud, 0.5))
+            };
+            let position = Vector::new(
+                (bounds.size().x - text_width(&text, TEXT_SCALE)) / 2.0,
+                top + i as Float * line_height,
+            );
+            draw_text(lines, canvas, &text, position, TEXT_SCALE, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_wraps_around() {
+        let mut menu = PauseMenu::default();
+        assert_eq!(menu.selected(), PauseOption::Resume);
+        assert_eq!(menu.handle_key(Keycode::Up), None);
+        assert_eq!(menu.selected(), PauseOption::Quit);
+        assert_eq!(menu.handle_key(Keycode::Down), None);
+        assert_eq!(menu.selected(), PauseOption::Resume);
+    }
+
+    #[test]
+    fn enter_activates_selection() {
+        let mut menu = PauseMenu::default();
+        menu.handle_key(Keycode::Down);
+        // Other keys neither move nor choose
+        assert_eq!(menu.handle_key(Keycode::Space), None);
+        assert_eq!(menu.handle_key(Keycode::Return), Some(PauseOption::Restart));
+        menu.handle_key(Keycode::Down);
+        assert_eq!(
+            menu.handle_key(Keycode::KpEnter),
+            Some(PauseOption::Difficulty)
+        );
+    }
+
+    #[test]
+    fn difficulty_label_names_the_preset() {
+        assert_eq!(
+            PauseOption::Difficulty.label(&Difficulty::HARD),
+            "Difficulty: hard"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Timing frames and the ticks simulated in them.
+//!
+//! With the `tokio` feature (the default), paced frames are timed by a tokio interval on a
+//! runtime thread. Without it, the main thread sleeps until each frame's deadline instead.
+
+use std::time::Duration;
+
+/// The most ticks simulated in one frame when catching up, so a long stall doesn't make every
+/// frame after it slow too
+pub const MAX_CATCH_UP_TICKS: u32 = 5;
+
+/// How much of some unsimulated time to simulate this frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatchUp {
+    /// Whole ticks to simulate now, at most the `max_ticks` asked for
+    pub ticks: u32,
+    /// Time short of a whole tick, to carry over to the next frame
+    pub leftover: Duration,
+    /// Time beyond `max_ticks` whole ticks, which is never simulated
+    pub dropped: Duration,
+}
+
+/// Splits `unsimulated` time into ticks of `tick_length`, simulating at most `max_ticks` of them.
+pub fn catch_up(unsimulated: Duration, tick_length: Duration, max_ticks: u32) -> CatchUp {
+    let whole_ticks = unsimulated.as_nanos() / tick_length.as_nanos();
+    let ticks = whole_ticks.min(max_ticks.into()) as u32;
+    let leftover = unsimulated - tick_length * whole_ticks as u32;
+    CatchUp {
+        ticks,
+        leftover,
+        dropped: unsimulated - leftover - tick_length * ticks,
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use tokio_pacer::Pacer;
+
+#[cfg(not(feature = "tokio"))]
+pub use sleep_pacer::Pacer;
+
+#[cfg(feature = "tokio")]
+mod tokio_pacer {
+    use std::time::Duration;
+
+    /// Stops the tokio runtime thread and waits for it when dropped, so it is stopped however
+    /// the game ends, including by panicking.
+    struct RuntimeGuard {
+        stop_tx: tokio::sync::watch::Sender<bool>,
+        /// `None` once joined
+        thread: Option<std::thread::JoinHandle<()>>,
+    }
+
+    impl Drop for RuntimeGuard {
+        fn drop(&mut self) {
+            self.stop_tx.send(true).ok();
+            if let Some(thread) = self.thread.take() {
+                // Not `unwrap`, which could panic while already unwinding from a panic
+                if thread.join().is_err() {
+                    eprintln!("The tokio runtime thread panicked");
+                }
+            }
+        }
+    }
+
+    pub struct Pacer {
+        handle: tokio::runtime::Handle,
+        frame_interval: tokio::time::Interval,
+        // Dropped last, after everything using the runtime
+        _runtime_guard: RuntimeGuard,
+    }
+
+    impl Pacer {
+        pub fn new(interval: Duration) -> Result<Pacer, String> {
+            let (handle_tx, handle_rx) = tokio::sync::oneshot::channel();
+            let runtime_thread = std::thread::spawn(move || {
+                let runtime =
+                    tokio::runtime::Runtime::new().expect("Failed to initialize tokio runtime");
+                let runtime = &runtime;
+                runtime.block_on(async move {
+                    let (stop_tx, mut stop_rx) = tokio::sync::watch::channel(false);
+                    handle_tx.send((stop_tx, runtime.handle().clone())).unwrap();
+                    let mut interval = tokio::time::interval(interval);
+                    loop {
+                        if *stop_rx.borrow_and_update() {
+                            break;
+                        }
+                        interval.tick().await;
+                    }
+                })
+            });
+
+            // Not really used yet, except for keeping the frame interval mostly constant.
+            let Ok((stop_tx, handle)) = handle_rx.blocking_recv() else {
+                return Err("Failed to initialize tokio runtime".to_owned());
+            };
+            let runtime_guard = RuntimeGuard {
+                stop_tx,
+                thread: Some(runtime_thread),
+            };
+            let mut frame_interval = {
+                let _enterguard = handle.enter();
+                tokio::time::interval(interval)
+            };
+            // Rather than rushing through frames missed during a stall
+            frame_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            Ok(Pacer {
+                handle,
+                frame_interval,
+                _runtime_guard: runtime_guard,
+            })
+        }
+
+        /// Waits until the next frame is due, returning how late it was already.
+        pub fn wait(&mut self) -> Duration {
+            let deadline = self.handle.block_on(self.frame_interval.tick());
+            tokio::time::Instant::now().saturating_duration_since(deadline)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        use super::*;
+
+        #[test]
+        fn runtime_guard_stops_and_joins_thread_when_dropped() {
+            let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+            let observer = stop_tx.subscribe();
+            let stopped = Arc::new(AtomicBool::new(false));
+            let thread = std::thread::spawn({
+                let stopped = stopped.clone();
+                move || {
+                    while !*stop_rx.borrow() {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                    stopped.store(true, Ordering::SeqCst);
+                }
+            });
+            let guard = RuntimeGuard {
+                stop_tx,
+                thread: Some(thread),
+            };
+
+            assert!(!*observer.borrow());
+            drop(guard);
+            assert!(*observer.borrow());
+            // Dropping waited for the thread to finish
+            assert!(stopped.load(Ordering::SeqCst));
+        }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+mod sleep_pacer {
+    use std::time::{Duration, Instant};
+
+    /// Returns when the frame after the one due at `deadline` is due, given the time is `now`.
+    ///
+    /// Frames missed by falling more than a whole `interval` behind are skipped rather than rushed
+    /// through, so the next frame is due within `interval` of `now`.
+    pub fn next_deadline(deadline: Instant, interval: Duration, now: Instant) -> Instant {
+        let next = deadline + interval;
+        if next + interval <= now {
+            now + interval
+        } else {
+            next
+        }
+    }
+
+    /// Returns how long to sleep at `now` to wake at `deadline`, which is zero if it has passed.
+    pub fn sleep_duration(now: Instant, deadline: Instant) -> Duration {
+        deadline.saturating_duration_since(now)
+    }
+
+    pub struct Pacer {
+        interval: Duration,
+        /// When the next frame is due
+        deadline: Instant,
+    }
+
+    impl Pacer {
+        pub fn new(interval: Duration) -> Result<Pacer, String> {
+            Ok(Pacer {
+                interval,
+                deadline: Instant::now() + interval,
+            })
+        }
+
+        /// Waits until the next frame is due, returning how late it was already.
+        pub fn wait(&mut self) -> Duration {
+            let now = Instant::now();
+            let overrun = now.saturating_duration_since(self.deadline);
+            std::thread::sleep(sleep_duration(now, self.deadline));
+            self.deadline = next_deadline(self.deadline, self.interval, Instant::now());
+            overrun
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const INTERVAL: Duration = Duration::from_millis(16);
+
+        #[test]
+        fn sleeps_until_deadline_or_not_at_all() {
+            let now = Instant::now();
+            let deadline = now + Duration::from_millis(5);
+            assert_eq!(sleep_duration(now, deadline), Duration::from_millis(5));
+            assert_eq!(sleep_duration(deadline, deadline), Duration::ZERO);
+            assert_eq!(
+                sleep_duration(deadline + Duration::from_millis(3), deadline),
+                Duration::ZERO
+            );
+        }
+
+        #[test]
+        fn next_deadline_keeps_pace() {
+            let deadline = Instant::now();
+            // Slightly late frames keep to the schedule, to catch up
+            let late = deadline + Duration::from_millis(10);
+            assert_eq!(next_deadline(deadline, INTERVAL, late), deadline + INTERVAL);
+        }
+
+        #[test]
+        fn next_deadline_skips_missed_frames() {
+            let deadline = Instant::now();
+            let stalled = deadline + INTERVAL * 5;
+            assert_eq!(
+                next_deadline(deadline, INTERVAL, stalled),
+                stalled + INTERVAL
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn catch_up_simulates_whole_ticks_and_carries_the_rest() {
+        assert_eq!(
+            catch_up(Duration::from_millis(25), TICK, MAX_CATCH_UP_TICKS),
+            CatchUp {
+                ticks: 2,
+                leftover: Duration::from_millis(5),
+                dropped: Duration::ZERO,
+            }
+        );
+    }
+
+    #[test]
+    fn catch_up_is_capped_after_a_stall() {
+        assert_eq!(
+            catch_up(Duration::from_millis(1003), TICK, MAX_CATCH_UP_TICKS),
+            CatchUp {
+                ticks: MAX_CATCH_UP_TICKS,
+                leftover: Duration::from_millis(3),
+                dropped: TICK * (100 - MAX_CATCH_UP_TICKS),
+            }
+        );
+    }
+}
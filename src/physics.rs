@@ -0,0 +1,50 @@
+use crate::precision::{consts, Float};
+
+/// How ships handle and shoot, which can be tuned from the console while playing.
+#[derive(Debug, Clone, Copy)]
+pub struct Physics {
+    /// Speed gained each frame while thrusting, in pixels per frame
+    pub thrust: Float,
+    /// Fraction of their speed that things with drag lose each frame
+    pub drag: Float,
+    /// Rotations per second of a turning ship
+    pub turn_speed: Float,
+    /// Speed of a fired bullet relative to the ship that fired it, in pixels per frame
+    pub muzzle_speed: Float,
+    /// How many times their usual size bullets are, for both drawing and collisions
+    pub bullet_scale: Float,
+}
+
+impl Physics {
+    /// How far a turning ship rotates each frame at `fps`, in radians.
+    pub fn turn_rate(&self, fps: u32) -> Float {
+        consts::TAU * self.turn_speed / fps as Float
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            thrust: 0.1,
+            drag: 0.01,
+            turn_speed: 1.0 / 3.0,
+            muzzle_speed: 4.0,
+            bullet_scale: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turn_speed_is_independent_of_fps() {
+        let physics = Physics::default();
+        for fps in [30, 60, 144] {
+            let per_second = physics.turn_rate(fps) * fps as Float;
+            assert!((per_second - consts::TAU * physics.turn_speed).abs() < 1e-4);
+        }
+        assert!((physics.turn_rate(30) - 2.0 * physics.turn_rate(60)).abs() < 1e-6);
+    }
+}
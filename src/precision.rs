@@ -0,0 +1,20 @@
+//! The precision the game is simulated and drawn with: `f64` by default, or `f32` with the `f32`
+//! feature, for comparing performance on constrained hardware.
+
+#[cfg(not(feature = "f32"))]
+mod types {
+    pub use std::f64::consts;
+    pub type Float = f64;
+    pub type Vector = glam::DVec2;
+    pub type Matrix = glam::DMat2;
+}
+
+#[cfg(feature = "f32")]
+mod types {
+    pub use std::f32::consts;
+    pub type Float = f32;
+    pub type Vector = glam::Vec2;
+    pub type Matrix = glam::Mat2;
+}
+
+pub use types::*;
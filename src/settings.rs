@@ -0,0 +1,550 @@
+use sdl2::pixels::Color;
+use sdl2::render::CanvasBuilder;
+
+use crate::asteroid_shape::AsteroidShapeTable;
+use crate::bounds::WrapAxes;
+use crate::difficulty::Difficulty;
+use crate::physics::Physics;
+use crate::precision::Float;
+use crate::skin::Skin;
+use crate::theme::Theme;
+use crate::MAX_ASTEROID_SIZE;
+
+/// The most human players, one per control scheme.
+pub const MAX_PLAYERS: usize = 4;
+
+/// The closest together the debug grid's lines can be, in pixels
+const MIN_GRID_SPACING: Float = 10.0;
+
+pub const USAGE: &str = "\
+Usage: asteroids [OPTIONS]
+
+Options:
+    --antialias                        Draw smooth lines (needs SDL 2.0.18 or later)
+    --asteroid-shape <SIZE>:<VERTS>:<MIN>:<MAX>
+                                       Give asteroids of a size, from 1 to 3, VERTS corners
+                                       from MIN to MAX pixels from their center. Repeatable
+    --auto-fire                        Holding fire keeps shooting, a few times a second
+    --big-bang                         Start with the asteroids bursting out of the middle of the
+                                       screen, before the players take control
+    --bouncy-debris                    Debris bounces off asteroids
+    --bumper                           Asteroids knock players back instead of killing them
+    --conserve-mass                    Shrink the pieces of split asteroids to add up to the
+                                       area of the original
+    --cpu                              Add one more player, flown by the computer
+    --craters                          Draw a few craters inside each asteroid
+    --dampener                         Ships left alone for a moment slow to a stop
+    --debris-drag <F>                  Fraction of its speed debris loses each frame, from 0 to
+                                       below 1, separately from ships (default: 0.03)
+    --debug                            Clicking spawns an asteroid at the cursor, and scrolling
+                                       changes its size
+    --debug-log <PATH>                 Log entity spawns, removals, collisions and splits,
+                                       and the final score, to a file, or to stderr if PATH
+                                       is `-`
+    --difficulty <easy|normal|hard>    How quickly waves get harder (default: normal).
+                                       The pause menu changes it while playing
+    --flat-asteroids                   Draw all asteroids in the same color, regardless of size
+    --fps <N>                          Frames per second (default: 60)
+    --grid-spacing <PIXELS>            Distance between the lines of the grid drawn with the F3
+                                       debug overlay, at least 10 (default: 100)
+    --invert-y                         Draw the play area upside down, with y increasing upwards
+    --lives <N>                        Lives each player starts with, at least 1 (default: 3)
+    --pause-unfocused                  Pause while the window doesn't have focus
+    --players <N>                      Human players, from 1 to 4 (default: 2)
+    --practice                         Players are invincible
+    --present-mode <paced|vsync|uncapped>
+                                       How often to render (default: paced)
+    --render-scale <F>                 Draw this many times the detail the display calls for, with
+                                       lines as much thicker. With --resolution, draw the frame
+                                       this many times larger before scaling it up (default: 1)
+    --resolution <WIDTH>x<HEIGHT>      Play in an area this many pixels across, drawn blockily
+                                       scaled up by a whole number to fit the window
+    --round-points                     Round to the nearest pixel when drawing, instead of truncating
+    --ricochet                         Bullets bounce off the screen edges instead of wrapping
+    --seed <N>                         Seed the random number generator, for reproducible games
+    --skin <PATH>                      Load hand-made asteroid shapes and colors from a file, as
+                                       lines of `shape <SIZE> <X>,<Y> <X>,<Y> ...` and
+                                       `color <SIZE> <RRGGBB>`
+    --spread-angle <DEGREES>           Width of the spread weapon's arc, from 0 to 360
+                                       (default: 30)
+    --spread-pellets <N>               Bullets fired per spread shot (default: 5)
+    --stable-order                     Update entities in the order they were created, rather
+                                       than however they happen to be stored, for reproducible
+                                       games with --seed
+    --survival                         Asteroids keep arriving, ever faster, instead of in waves
+    --trail-fade <F>                   Fraction of each frame left visible in the next, from 0 to
+                                       below 1, so moving things leave trails (default: 0)
+    --time-attack <SECONDS>            Score as much as possible before the clock runs out.
+                                       Destroying asteroids adds a little time. Once it is up,
+                                       Enter plays again
+    --title <TEXT>                     Window title, followed by the score and wave
+                                       (default: Asteroids)
+    --theme <classic|neon|deuteranopia|protanopia>
+                                       Colors to draw with (default: classic).
+                                       F4 cycles through them while playing
+    --wrap <both|horizontal|vertical|none>
+                                       Which edges wrap around; the others are drawn as walls
+                                       that things bounce off (default: both)
+    --help                             Print this message";
+
+/// Settings chosen at startup, from the command line.
+#[derive(Debug)]
+pub struct Settings {
+    /// Draw lines as anti-aliased geometry.
+    pub antialias: bool,
+    /// How many corners each size of asteroid has, and how jagged it is.
+    pub asteroid_shapes: AsteroidShapeTable,
+    /// Colors to draw each asteroid size in whatever the theme, from a skin, indexed by size - 1.
+    pub asteroid_colors: [Option<Color>; MAX_ASTEROID_SIZE],
+    /// Holding the fire key fires repeatedly, rather than once per press.
+    pub auto_fire: bool,
+    /// Start each game with a short intro of the asteroids bursting out of the middle of the
+    /// screen.
+    pub big_bang: bool,
+    /// Debris bounces off asteroids, rather than passing through everything.
+    pub bouncy_debris: bool,
+    /// Players bump off asteroids instead of dying.
+    pub bumper: bool,
+    /// Scale the children of a split asteroid so their total area matches their parent's.
+    pub conserve_mass: bool,
+    /// Add a computer-controlled player.
+    pub cpu_player: bool,
+    /// Draw craters inside asteroids.
+    pub craters: bool,
+    /// Ships that are neither thrusting nor turning slow down quickly.
+    pub dampener: bool,
+    /// Fraction of its speed debris loses each frame, independent of `Physics::drag`.
+    pub debris_drag: Float,
+    /// Enable debug tools, such as spawning asteroids by clicking.
+    pub debug: bool,
+    /// Where to write the entity event log, if anywhere.
+    pub debug_log: Option<String>,
+    pub difficulty: Difficulty,
+    /// Target frame rate. Never zero.
+    pub fps: u32,
+    /// Distance between the debug overlay's grid lines, in pixels. Never below `MIN_GRID_SPACING`.
+    pub grid_spacing: Float,
+    /// Draw the play area mirrored top to bottom, as in the usual mathematical convention.
+    pub invert_y: bool,
+    /// Lives each player starts with. Never zero.
+    pub lives: u32,
+    /// Pause when the window loses focus, and resume when it regains it.
+    pub pause_unfocused: bool,
+    /// Human players, from 1 to `MAX_PLAYERS`.
+    pub players: usize,
+    /// Not set from the command line, but can be tuned from the console while playing.
+    pub physics: Physics,
+    /// Players can't die, for studying collisions and physics.
+    pub practice: bool,
+    pub present_mode: PresentMode,
+    /// Multiplies how many pixels each unit of the play area is drawn across. Always above 0.
+    pub render_scale: Float,
+    /// The fixed width and height of the play area, drawn scaled up to fit the window, or `None`
+    /// for the play area to be the size of the window.
+    pub resolution: Option<(u32, u32)>,
+    /// Bullets bounce off the screen edges a few times instead of wrapping.
+    pub ricochet: bool,
+    /// Round positions to the nearest pixel when drawing, instead of truncating.
+    pub round_points: bool,
+    /// Random seed, or `None` to seed from entropy.
+    pub seed: Option<u64>,
+    /// Width of the spread weapon's arc, in radians.
+    pub spread_angle: Float,
+    /// Bullets fired per spread shot. Never zero.
+    pub spread_pellets: u32,
+    /// Step entities in id order each tick, rather than in storage order.
+    pub stable_order: bool,
+    /// Asteroids arrive continuously at an increasing rate, rather than in waves.
+    pub survival: bool,
+    pub theme: Theme,
+    /// Length of the time attack clock in seconds, or `None` to play without one.
+    pub time_attack: Option<Float>,
+    /// Shown at the start of the window title, before the score and wave.
+    pub title: String,
+    /// Fraction of each frame that stays visible in the next. 0 clears every frame.
+    pub trail_fade: Float,
+    /// Which edges of the play area wrap.
+    pub wrap: WrapAxes,
+}
+
+/// How rendering is timed.
+///
+/// The simulation always runs at `Settings::fps` ticks per second.
+#[derive(Debug, Clone, Copy)]
+pub enum PresentMode {
+    /// Render once per simulation tick, waiting for the next tick after each frame.
+    Paced,
+    /// Render once per display refresh.
+    Vsync,
+    /// Render as fast as possible.
+    Uncapped,
+}
+
+impl PresentMode {
+    /// Whether presenting a frame waits for the display to refresh.
+    pub fn vsync(self) -> bool {
+        match self {
+            PresentMode::Paced | PresentMode::Uncapped => false,
+            PresentMode::Vsync => true,
+        }
+    }
+
+    pub fn configure(self, builder: CanvasBuilder) -> CanvasBuilder {
+        if self.vsync() {
+            builder.present_vsync()
+        } else {
+            builder
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            antialias: false,
+            asteroid_shapes: AsteroidShapeTable::default(),
+            asteroid_colors: [None; MAX_ASTEROID_SIZE],
+            auto_fire: false,
+            big_bang: false,
+            bouncy_debris: false,
+            bumper: false,
+            conserve_mass: false,
+            cpu_player: false,
+            craters: false,
+            dampener: false,
+            debris_drag: 0.03,
+            debug: false,
+            debug_log: None,
+            difficulty: Difficulty::default(),
+            fps: 60,
+            grid_spacing: 100.0,
+            invert_y: false,
+            lives: 3,
+            pause_unfocused: false,
+            players: 2,
+            physics: Physics::default(),
+            practice: false,
+            present_mode: PresentMode::Paced,
+            render_scale: 1.0,
+            resolution: None,
+            ricochet: false,
+            round_points: false,
+            seed: None,
+            spread_angle: Float::to_radians(30.0),
+            spread_pellets: 5,
+            stable_order: false,
+            survival: false,
+            theme: Theme::default(),
+            time_attack: None,
+            title: "Asteroids".to_owned(),
+            trail_fade: 0.0,
+            wrap: WrapAxes::BOTH,
+        }
+    }
+}
+
+impl Settings {
+    /// Converts a duration in seconds to a whole number of frames.
+    pub fn frames(&self, seconds: Float) -> u64 {
+        (seconds * self.fps as Float).round() as u64
+    }
+
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        // Applied after parsing, so it doesn't matter whether it comes before `--theme`
+        let mut flat_asteroids = false;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for `{arg}`"))
+            };
+            match &*arg {
+                "--antialias" => settings.antialias = true,
+                "--asteroid-shape" => settings.asteroid_shapes.set_from_arg(&value()?)?,
+                "--auto-fire" => settings.auto_fire = true,
+                "--big-bang" => settings.big_bang = true,
+                "--bouncy-debris" => settings.bouncy_debris = true,
+                "--bumper" => settings.bumper = true,
+                "--conserve-mass" => settings.conserve_mass = true,
+                "--cpu" => settings.cpu_player = true,
+                "--craters" => settings.craters = true,
+                "--dampener" => settings.dampener = true,
+                "--debris-drag" => {
+                    let value = value()?;
+                    settings.debris_drag = match value.parse() {
+                        Ok(drag) if (0.0..1.0).contains(&drag) => drag,
+                        Ok(_) => {
+                            return Err("`--debris-drag` must be at least 0 and below 1".to_owned())
+                        }
+                        Err(e) => return Err(format!("Invalid debris drag `{value}`: {e}")),
+                    };
+                }
+                "--debug" => settings.debug = true,
+                "--debug-log" => settings.debug_log = Some(value()?),
+                "--difficulty" => {
+                    let value = value()?;
+                    settings.difficulty = Difficulty::preset(&value)
+                        .ok_or_else(|| format!("Unknown difficulty `{value}`"))?;
+                }
+                "--flat-asteroids" => flat_asteroids = true,
+                "--fps" => {
+                    let value = value()?;
+                    settings.fps = match value.parse() {
+                        Ok(0) => return Err("`--fps` must be at least 1".to_owned()),
+                        Ok(fps) => fps,
+                        Err(e) => return Err(format!("Invalid fps `{value}`: {e}")),
+                    };
+                }
+                "--grid-spacing" => {
+                    let value = value()?;
+                    settings.grid_spacing = match value.parse() {
+                        Ok(spacing) if (MIN_GRID_SPACING..Float::INFINITY).contains(&spacing) => {
+                            spacing
+                        }
+                        Ok(_) => {
+                            return Err(format!(
+                                "`--grid-spacing` must be at least {MIN_GRID_SPACING}"
+                            ))
+                        }
+                        Err(e) => return Err(format!("Invalid grid spacing `{value}`: {e}")),
+                    };
+                }
+                "--players" => {
+                    let value = value()?;
+                    settings.players = match value.parse() {
+                        Ok(players @ 1..=MAX_PLAYERS) => players,
+                        Ok(_) => {
+                            return Err(format!("`--players` must be from 1 to {MAX_PLAYERS}"))
+                        }
+                        Err(e) => return Err(format!("Invalid player count `{value}`: {e}")),
+                    };
+                }
+                "--invert-y" => settings.invert_y = true,
+                "--lives" => {
+                    let value = value()?;
+                    settings.lives = match value.parse() {
+                        Ok(0) => return Err("`--lives` must be at least 1".to_owned()),
+                        Ok(lives) => lives,
+                        Err(e) => return Err(format!("Invalid lives `{value}`: {e}")),
+                    };
+                }
+                "--pause-unfocused" => settings.pause_unfocused = true,
+                "--practice" => settings.practice = true,
+                "--present-mode" => {
+                    let value = value()?;
+                    settings.present_mode = match &*value {
+                        "paced" => PresentMode::Paced,
+                        "vsync" => PresentMode::Vsync,
+                        "uncapped" => PresentMode::Uncapped,
+                        _ => return Err(format!("Unknown present mode `{value}`")),
+                    };
+                }
+                "--render-scale" => {
+                    let value = value()?;
+                    settings.render_scale = match value.parse() {
+                        Ok(scale) if scale > 0.0 => scale,
+                        Ok(_) => return Err("`--render-scale` must be above 0".to_owned()),
+                        Err(e) => return Err(format!("Invalid render scale `{value}`: {e}")),
+                    };
+                }
+                "--resolution" => {
+                    let value = value()?;
+                    let invalid = || format!("Invalid resolution `{value}`, expected `<W>x<H>`");
+                    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+                    settings.resolution = match (width.parse(), height.parse()) {
+                        (Ok(width @ 1..), Ok(height @ 1..)) => Some((width, height)),
+                        _ => return Err(invalid()),
+                    };
+                }
+                "--theme" => {
+                    let value = value()?;
+                    settings.theme =
+                        Theme::preset(&value).ok_or_else(|| format!("Unknown theme `{value}`"))?;
+                }
+                "--time-attack" => {
+                    let value = value()?;
+                    settings.time_attack = match value.parse() {
+                        Ok(seconds) if seconds > 0.0 => Some(seconds),
+                        Ok(_) => return Err("`--time-attack` must be above 0".to_owned()),
+                        Err(e) => return Err(format!("Invalid time `{value}`: {e}")),
+                    };
+                }
+                "--title" => settings.title = value()?,
+                "--trail-fade" => {
+                    let value = value()?;
+                    settings.trail_fade = match value.parse() {
+                        Ok(fade) if (0.0..1.0).contains(&fade) => fade,
+                        Ok(_) => {
+                            return Err("`--trail-fade` must be at least 0 and below 1".to_owned())
+                        }
+                        Err(e) => return Err(format!("Invalid trail fade `{value}`: {e}")),
+                    };
+                }
+                "--wrap" => {
+                    let value = value()?;
+                    settings.wrap = WrapAxes::preset(&value)
+                        .ok_or_else(|| format!("Unknown wrapping `{value}`"))?;
+                }
+                "--ricochet" => settings.ricochet = true,
+                "--round-points" => settings.round_points = true,
+                "--seed" => {
+                    let value = value()?;
+                    settings.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|e| format!("Invalid seed `{value}`: {e}"))?,
+                    );
+                }
+                "--skin" => {
+                    let skin = Skin::load(&value()?)?;
+                    settings.asteroid_shapes.set_templates(skin.shapes);
+                    settings.asteroid_colors = skin.colors;
+                }
+                "--spread-angle" => {
+                    let value = value()?;
+                    let degrees: Float = match value.parse() {
+                        Ok(degrees) if (0.0..=360.0).contains(&degrees) => degrees,
+                        Ok(_) => return Err("`--spread-angle` must be from 0 to 360".to_owned()),
+                        Err(e) => return Err(format!("Invalid spread angle `{value}`: {e}")),
+                    };
+                    settings.spread_angle = degrees.to_radians();
+                }
+                "--spread-pellets" => {
+                    let value = value()?;
+                    settings.spread_pellets = match value.parse() {
+                        Ok(0) => return Err("`--spread-pellets` must be at least 1".to_owned()),
+                        Ok(pellets) => pellets,
+                        Err(e) => return Err(format!("Invalid spread pellets `{value}`: {e}")),
+                    };
+                }
+                "--stable-order" => settings.stable_order = true,
+                "--survival" => settings.survival = true,
+                "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => return Err(format!("Unrecognized argument `{arg}`\n\n{USAGE}")),
+            }
+        }
+        if flat_asteroids {
+            settings.theme.shade_asteroids_by_size = false;
+        }
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Settings, String> {
+        Settings::from_args(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    #[test]
+    fn frames_round_seconds_at_the_configured_rate() {
+        let settings = parse(&["--fps", "144"]).unwrap();
+        assert_eq!(settings.frames(1.0), 144);
+        assert_eq!(settings.frames(0.5), 72);
+        assert!(parse(&["--fps", "0"]).is_err());
+    }
+
+    #[test]
+    fn present_mode_is_chosen_by_name() {
+        let mode = |name| parse(&["--present-mode", name]).map(|settings| settings.present_mode);
+        assert!(matches!(
+            parse(&[]).unwrap().present_mode,
+            PresentMode::Paced
+        ));
+        assert!(matches!(mode("vsync"), Ok(PresentMode::Vsync)));
+        assert!(matches!(mode("uncapped"), Ok(PresentMode::Uncapped)));
+        assert!(mode("adaptive").is_err());
+    }
+
+    #[test]
+    fn present_modes_choose_vsync() {
+        let vsync = |name| {
+            parse(&["--present-mode", name])
+                .unwrap()
+                .present_mode
+                .vsync()
+        };
+        assert!(!vsync("paced"));
+        assert!(vsync("vsync"));
+        assert!(!vsync("uncapped"));
+    }
+
+    #[test]
+    fn seed_is_optional() {
+        assert_eq!(parse(&[]).unwrap().seed, None);
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert!(parse(&["--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn spread_angle_is_given_in_degrees() {
+        let settings = parse(&["--spread-angle", "90", "--spread-pellets", "3"]).unwrap();
+        assert!((settings.spread_angle - crate::precision::consts::FRAC_PI_2).abs() < 1e-12);
+        assert_eq!(settings.spread_pellets, 3);
+        assert!(parse(&["--spread-pellets", "0"]).is_err());
+        assert!(parse(&["--spread-angle", "360"]).is_ok());
+        for degrees in ["-1", "361", "NaN", "inf"] {
+            assert!(parse(&["--spread-angle", degrees]).is_err(), "{degrees}");
+        }
+    }
+
+    #[test]
+    fn players_are_limited_to_the_control_schemes() {
+        assert_eq!(parse(&["--players", "4"]).unwrap().players, MAX_PLAYERS);
+        assert!(parse(&["--players", "0"]).is_err());
+        assert!(parse(&["--players", "5"]).is_err());
+    }
+
+    #[test]
+    fn wrap_is_chosen_by_name() {
+        assert_eq!(parse(&[]).unwrap().wrap, WrapAxes::BOTH);
+        assert_eq!(
+            parse(&["--wrap", "horizontal"]).unwrap().wrap,
+            WrapAxes { x: true, y: false }
+        );
+        assert!(parse(&["--wrap", "diagonal"]).is_err());
+    }
+
+    #[test]
+    fn time_attack_needs_a_positive_length() {
+        assert_eq!(parse(&[]).unwrap().time_attack, None);
+        assert_eq!(
+            parse(&["--time-attack", "90"]).unwrap().time_attack,
+            Some(90.0)
+        );
+        assert!(parse(&["--time-attack", "0"]).is_err());
+        assert!(parse(&["--time-attack", "NaN"]).is_err());
+    }
+
+    #[test]
+    fn lives_must_be_at_least_one() {
+        assert_eq!(parse(&[]).unwrap().lives, 3);
+        assert_eq!(parse(&["--lives", "1"]).unwrap().lives, 1);
+        assert!(parse(&["--lives", "0"]).is_err());
+        assert!(parse(&["--lives", "many"]).is_err());
+    }
+
+    #[test]
+    fn grid_spacing_has_a_minimum() {
+        assert_eq!(parse(&[]).unwrap().grid_spacing, 100.0);
+        assert_eq!(
+            parse(&["--grid-spacing", "10"]).unwrap().grid_spacing,
+            MIN_GRID_SPACING
+        );
+        for spacing in ["9.9", "0", "-50", "NaN", "inf"] {
+            assert!(parse(&["--grid-spacing", spacing]).is_err(), "{spacing}");
+        }
+    }
+
+    #[test]
+    fn title_defaults_to_the_game_name() {
+        assert_eq!(parse(&[]).unwrap().title, "Asteroids");
+        assert_eq!(parse(&["--title", "Rocks"]).unwrap().title, "Rocks");
+        assert!(parse(&["--title"]).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Hand-made asteroid shapes and colors, loaded from a file with `--skin`.
+//!
+//! Each line of a skin file is blank, a `#` comment, or one of:
+//!
+//! - `shape <SIZE> <X>,<Y> <X>,<Y> ...`, a shape for asteroids of that size, as its verts in order
+//!   around its center. Asteroids of a size are each given one of its shapes at random, or a
+//!   generated one as usual if it has none.
+//! - `color <SIZE> <RRGGBB>`, the color to draw asteroids of that size in, whatever the theme.
+
+use std::sync::Arc;
+
+use either::Either;
+use sdl2::pixels::Color;
+
+use crate::precision::Vector;
+use crate::{Bounding, MAX_ASTEROID_SIZE};
+
+/// The shapes and colors of a skin file, indexed by size - 1.
+#[derive(Debug, Default)]
+pub struct Skin {
+    pub shapes: [Vec<Arc<[Vector]>>; MAX_ASTEROID_SIZE],
+    pub colors: [Option<Color>; MAX_ASTEROID_SIZE],
+}
+
+impl Skin {
+    pub fn load(path: &str) -> Result<Skin, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read skin `{path}`: {e}"))?;
+        Skin::parse(&text).map_err(|e| format!("Invalid skin `{path}`: {e}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Skin, String> {
+        let mut skin = Skin::default();
+        for (number, line) in (1..).zip(text.lines()) {
+            let mut words = line.split_whitespace();
+            let Some(kind) = words.next().filter(|word| !word.starts_with('#')) else {
+                continue;
+            };
+            let size = match words.next().map(str::parse) {
+                Some(Ok(size @ 1..=MAX_ASTEROID_SIZE)) => size,
+                _ => {
+                    return Err(format!(
+                        "line {number}: expected a size from 1 to {MAX_ASTEROID_SIZE}"
+                    ))
+                }
+            };
+            match kind {
+                "shape" => {
+                    let verts = words
+                        .map(|vert| parse_vert(vert).ok_or(vert))
+                        .collect::<Result<Arc<[Vector]>, _>>()
+                        .map_err(|vert| format!("line {number}: invalid vert `{vert}`"))?;
+                    Bounding::cyclic(Either::Right(verts.clone()))
+                        .map_err(|e| format!("line {number}: the verts enclose no area ({e:?})"))?;
+                    skin.shapes[size - 1].push(verts);
+                }
+                "color" => {
+                    let color = words.next().and_then(parse_color);
+                    let (Some(color), None) = (color, words.next()) else {
+                        return Err(format!("line {number}: expected one color, as `RRGGBB`"));
+                    };
+                    skin.colors[size - 1] = Some(color);
+                }
+                _ => return Err(format!("line {number}: unknown `{kind}`")),
+            }
+        }
+        Ok(skin)
+    }
+}
+
+/// Parses `<X>,<Y>`.
+fn parse_vert(vert: &str) -> Option<Vector> {
+    let (x, y) = vert.split_once(',')?;
+    Some(Vector::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Parses `RRGGBB`, in hexadecimal.
+fn parse_color(color: &str) -> Option<Color> {
+    if color.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(color, 16).ok()?;
+    Some(Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_shapes_and_colors_by_size() {
+        let skin = Skin::parse(
+            "# Two shapes for the smallest size\n\
+             \n\
+             shape 1 0,-10 10,10 -10,10\n\
+             shape 1 -5,-5 5,-5 5,5 -5,5\n\
+             color 3 00ff80\n",
+        )
+        .unwrap();
+        assert_eq!(skin.shapes[0].len(), 2);
+        assert_eq!(skin.shapes[0][1].len(), 4);
+        assert!(skin.shapes[1].is_empty());
+        assert_eq!(
+            skin.colors,
+            [None, None, Some(Color::RGB(0x00, 0xff, 0x80))]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_lines() {
+        for text in [
+            "shape 0 0,0 1,0 0,1",
+            "shape 1 0,0 1,0 0,x",
+            // Flat, so it encloses no area
+            "shape 1 0,0 1,0 2,0",
+            "color 2 red",
+            "color 2 ff0000 00ff00",
+            "glow 2 ff0000",
+        ] {
+            assert!(Skin::parse(text).is_err(), "{text}");
+        }
+    }
+}
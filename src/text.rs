@@ -0,0 +1,167 @@
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, RenderTarget};
+
+use crate::lines::LineRenderer;
+use crate::precision::{Float, Vector};
+
+/// Width of a glyph, in grid units
+const GLYPH_WIDTH: Float = 4.0;
+/// Height of a glyph, in grid units
+pub const GLYPH_HEIGHT: Float = 6.0;
+/// Distance from the start of one glyph to the start of the next, in grid units
+const ADVANCE: Float = GLYPH_WIDTH + 2.0;
+
+/// Returns the strokes of a character as `[x1, y1, x2, y2]` on a grid `GLYPH_WIDTH` wide and
+/// `GLYPH_HEIGHT` tall, with y increasing downwards.
+///
+/// Letters are drawn in upper case. Unsupported characters have no strokes.
+fn glyph(c: char) -> &'static [[u8; 4]] {
+    match c.to_ascii_uppercase() {
+        'A' => &[
+            [0, 6, 0, 2],
+            [0, 2, 2, 0],
+            [2, 0, 4, 2],
+            [4, 2, 4, 6],
+            [0, 3, 4, 3],
+        ],
+        'B' => &[
+            [0, 0, 0, 6],
+            [0, 0, 3, 0],
+            [3, 0, 4, 1],
+            [4, 1, 4, 2],
+            [4, 2, 3, 3],
+            [0, 3, 3, 3],
+            [3, 3, 4, 4],
+            [4, 4, 4, 5],
+            [4, 5, 3, 6],
+            [3, 6, 0, 6],
+        ],
+        'C' => &[[4, 0, 0, 0], [0, 0, 0, 6], [0, 6, 4, 6]],
+        'D' => &[
+            [0, 0, 0, 6],
+            [0, 0, 2, 0],
+            [2, 0, 4, 2],
+            [4, 2, 4, 4],
+            [4, 4, 2, 6],
+            [2, 6, 0, 6],
+        ],
+        'E' => &[[4, 0, 0, 0], [0, 0, 0, 6], [0, 6, 4, 6], [0, 3, 3, 3]],
+        'F' => &[[4, 0, 0, 0], [0, 0, 0, 6], [0, 3, 3, 3]],
+        'G' => &[
+            [4, 0, 0, 0],
+            [0, 0, 0, 6],
+            [0, 6, 4, 6],
+            [4, 6, 4, 3],
+            [4, 3, 2, 3],
+        ],
+        'H' => &[[0, 0, 0, 6], [4, 0, 4, 6], [0, 3, 4, 3]],
+        'I' => &[[0, 0, 4, 0], [2, 0, 2, 6], [0, 6, 4, 6]],
+        'J' => &[[4, 0, 4, 6], [4, 6, 0, 6], [0, 6, 0, 4]],
+        'K' => &[[0, 0, 0, 6], [4, 0, 0, 3], [0, 3, 4, 6]],
+        'L' => &[[0, 0, 0, 6], [0, 6, 4, 6]],
+        'M' => &[[0, 6, 0, 0], [0, 0, 2, 3], [2, 3, 4, 0], [4, 0, 4, 6]],
+        'N' => &[[0, 6, 0, 0], [0, 0, 4, 6], [4, 6, 4, 0]],
+        'O' => &[[0, 0, 4, 0], [4, 0, 4, 6], [4, 6, 0, 6], [0, 6, 0, 0]],
+        'P' => &[[0, 6, 0, 0], [0, 0, 4, 0], [4, 0, 4, 3], [4, 3, 0, 3]],
+        'Q' => &[
+            [0, 0, 4, 0],
+            [4, 0, 4, 6],
+            [4, 6, 0, 6],
+            [0, 6, 0, 0],
+            [2, 4, 4, 6],
+        ],
+        'R' => &[
+            [0, 6, 0, 0],
+            [0, 0, 4, 0],
+            [4, 0, 4, 3],
+            [4, 3, 0, 3],
+            [2, 3, 4, 6],
+        ],
+        'S' | '5' => &[
+            [4, 0, 0, 0],
+            [0, 0, 0, 3],
+            [0, 3, 4, 3],
+            [4, 3, 4, 6],
+            [4, 6, 0, 6],
+        ],
+        'T' => &[[0, 0, 4, 0], [2, 0, 2, 6]],
+        'U' => &[[0, 0, 0, 6], [0, 6, 4, 6], [4, 6, 4, 0]],
+        'V' => &[[0, 0, 2, 6], [2, 6, 4, 0]],
+        'W' => &[[0, 0, 1, 6], [1, 6, 2, 3], [2, 3, 3, 6], [3, 6, 4, 0]],
+        'X' => &[[0, 0, 4, 6], [4, 0, 0, 6]],
+        'Y' => &[[0, 0, 2, 3], [4, 0, 2, 3], [2, 3, 2, 6]],
+        'Z' => &[[0, 0, 4, 0], [4, 0, 0, 6], [0, 6, 4, 6]],
+        '0' => &[
+            [0, 0, 4, 0],
+            [4, 0, 4, 6],
+            [4, 6, 0, 6],
+            [0, 6, 0, 0],
+            [4, 0, 0, 6],
+        ],
+        '1' => &[[1, 1, 2, 0], [2, 0, 2, 6], [0, 6, 4, 6]],
+        '2' => &[
+            [0, 0, 4, 0],
+            [4, 0, 4, 3],
+            [4, 3, 0, 3],
+            [0, 3, 0, 6],
+            [0, 6, 4, 6],
+        ],
+        '3' => &[[0, 0, 4, 0], [4, 0, 4, 6], [4, 6, 0, 6], [1, 3, 4, 3]],
+        '4' => &[[0, 0, 0, 3], [0, 3, 4, 3], [4, 0, 4, 6]],
+        '6' => &[
+            [4, 0, 0, 0],
+            [0, 0, 0, 6],
+            [0, 6, 4, 6],
+            [4, 6, 4, 3],
+            [4, 3, 0, 3],
+        ],
+        '7' => &[[0, 0, 4, 0], [4, 0, 1, 6]],
+        '8' => &[
+            [0, 0, 4, 0],
+            [4, 0, 4, 6],
+            [4, 6, 0, 6],
+            [0, 6, 0, 0],
+            [0, 3, 4, 3],
+        ],
+        '9' => &[
+            [4, 3, 0, 3],
+            [0, 3, 0, 0],
+            [0, 0, 4, 0],
+            [4, 0, 4, 6],
+            [4, 6, 0, 6],
+        ],
+        ':' => &[[2, 1, 2, 2], [2, 4, 2, 5]],
+        '-' => &[[1, 3, 3, 3]],
+        '.' => &[[2, 5, 2, 6]],
+        '/' => &[[4, 0, 0, 6]],
+        '>' => &[[1, 1, 3, 3], [3, 3, 1, 5]],
+        _ => &[],
+    }
+}
+
+/// Returns the width of `text` drawn at `scale` pixels per grid unit, in pixels.
+pub fn text_width(text: &str, scale: Float) -> Float {
+    match text.chars().count() {
+        0 => 0.0,
+        n => ((n - 1) as Float * ADVANCE + GLYPH_WIDTH) * scale,
+    }
+}
+
+/// Draws `text` with its top left corner at `position`, at `scale` pixels per grid unit.
+pub fn draw_text<T: RenderTarget>(
+    lines: &mut LineRenderer,
+    canvas: &mut Canvas<T>,
+    text: &str,
+    position: Vector,
+    scale: Float,
+    color: Color,
+) {
+    for (i, c) in text.chars().enumerate() {
+        let origin = position + Vector::new(i as Float * ADVANCE * scale, 0.0);
+        for &[x1, y1, x2, y2] in glyph(c) {
+            let p1 = origin + Vector::new(x1 as Float, y1 as Float) * scale;
+            let p2 = origin + Vector::new(x2 as Float, y2 as Float) * scale;
+            lines.line(canvas, p1, p2, color);
+        }
+    }
+}
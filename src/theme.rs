@@ -0,0 +1,207 @@
+use sdl2::pixels::Color;
+
+use crate::precision::Float;
+use crate::{lerp_color, shade, EntityKind, BOSS_ZONE_HEALTH, MAX_ASTEROID_SIZE};
+
+/// The colors everything is drawn in.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// The name of the preset this theme came from
+    pub name: &'static str,
+    pub background: Color,
+    pub ship: Color,
+    pub asteroid: Color,
+    pub bullet: Color,
+    pub debris: Color,
+    pub powerup: Color,
+    /// Overlays, such as the debug drawing
+    pub hud: Color,
+    /// If true, smaller asteroids are drawn dimmer than larger ones.
+    /// Otherwise all asteroids are drawn in `asteroid`.
+    pub shade_asteroids_by_size: bool,
+}
+
+impl Theme {
+    /// White on black, like the arcade original
+    pub fn classic() -> Theme {
+        Theme {
+            name: "classic",
+            background: Color::BLACK,
+            ship: Color::WHITE,
+            asteroid: Color::WHITE,
+            bullet: Color::WHITE,
+            debris: shade(Color::WHITE, 0.7),
+            powerup: Color::WHITE,
+            hud: Color::WHITE,
+            shade_asteroids_by_size: true,
+        }
+    }
+
+    pub fn neon() -> Theme {
+        let asteroid = Color::RGB(255, 0, 200);
+        Theme {
+            name: "neon",
+            background: Color::RGB(8, 0, 24),
+            ship: Color::RGB(0, 255, 255),
+            asteroid,
+            bullet: Color::RGB(255, 255, 0),
+            debris: shade(asteroid, 0.7),
+            powerup: Color::RGB(0, 255, 0),
+            hud: Color::RGB(0, 255, 128),
+            shade_asteroids_by_size: true,
+        }
+    }
+
+    /// For deuteranopia (red-green, weak green).
+    ///
+    /// Ships and asteroids differ in brightness as well as hue, so they are distinguishable
+    /// without telling red from green.
+    pub fn deuteranopia() -> Theme {
+        let asteroid = Color::RGB(0, 114, 178);
+        Theme {
+            name: "deuteranopia",
+            background: Color::BLACK,
+            ship: Color::RGB(240, 228, 66),
+            asteroid,
+            bullet: Color::WHITE,
+            debris: shade(asteroid, 0.7),
+            powerup: Color::RGB(86, 180, 233),
+            hud: Color::WHITE,
+            shade_asteroids_by_size: true,
+        }
+    }
+
+    /// For protanopia (red-green, weak red), which also makes reds look dark.
+    pub fn protanopia() -> Theme {
+        let asteroid = Color::RGB(0, 114, 178);
+        Theme {
+            name: "protanopia",
+            background: Color::BLACK,
+            ship: Color::WHITE,
+            asteroid,
+            bullet: Color::RGB(240, 228, 66),
+            debris: shade(asteroid, 0.7),
+            powerup: Color::RGB(0, 158, 115),
+            hud: Color::WHITE,
+            shade_asteroids_by_size: true,
+        }
+    }
+
+    /// The names of the presets, in the order `next` cycles through them.
+    pub const PRESETS: [&'static str; 4] = ["classic", "neon", "deuteranopia", "protanopia"];
+
+    pub fn preset(name: &str) -> Option<Theme> {
+        match name {
+            "classic" => Some(Theme::classic()),
+            "neon" => Some(Theme::neon()),
+            "deuteranopia" => Some(Theme::deuteranopia()),
+            "protanopia" => Some(Theme::protanopia()),
+            _ => None,
+        }
+    }
+
+    /// The preset after this one, keeping whether asteroids are shaded by size.
+    pub fn next(&self) -> Theme {
+        let index = Theme::PRESETS
+            .iter()
+            .position(|&name| name == self.name)
+            .map_or(0, |index| (index + 1) % Theme::PRESETS.len());
+        Theme {
+            shade_asteroids_by_size: self.shade_asteroids_by_size,
+            ..Theme::preset(Theme::PRESETS[index]).unwrap()
+        }
+    }
+
+    /// The largest asteroids are drawn in `asteroid`, and each smaller size is a bit dimmer.
+    pub fn asteroid_color(&self, size: usize) -> Color {
+        if !self.shade_asteroids_by_size {
+            return self.asteroid;
+        }
+        let steps_below_max = MAX_ASTEROID_SIZE.saturating_sub(size);
+        shade(self.asteroid, 1.0 - 0.2 * steps_below_max as Float)
+    }
+
+    pub fn color_of(&self, kind: &EntityKind) -> Color {
+        match kind {
+            // Tough asteroids flash when hit without splitting
+            EntityKind::Asteroid { flash: 1.., .. } => self.hud,
+            EntityKind::Asteroid { size, .. } => self.asteroid_color(*size),
+            // Bosses fade as their zones are destroyed
+            EntityKind::Boss { zones } => {
+                let health = zones.iter().sum::<u32>() as Float;
+                let max_health = (BOSS_ZONE_HEALTH as usize * zones.len()) as Float;
+                shade(self.asteroid, 0.4 + 0.6 * health / max_health)
+            }
+            EntityKind::Bullet { .. } => self.bullet,
+            // Debris fades into the background as it expires
+            EntityKind::Debris { ttl, lifetime } => lerp_color(
+                self.background,
+                self.debris,
+                *ttl as Float / *lifetime as Float,
+            ),
+            EntityKind::Player { .. } => self.ship,
+            EntityKind::Powerup { .. } => self.powerup,
+            EntityKind::Shockwave { .. } => self.bullet,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_change_asteroid_colors() {
+        let classic = Theme::preset("classic").unwrap();
+        let neon = Theme::preset("neon").unwrap();
+        for size in 1..=MAX_ASTEROID_SIZE {
+            assert_ne!(classic.asteroid_color(size), neon.asteroid_color(size));
+        }
+        assert_eq!(classic.next().name, "neon");
+    }
+
+    /// Perceived brightness, from 0 to 255.
+    fn luma(color: Color) -> f64 {
+        0.299 * color.r as f64 + 0.587 * color.g as f64 + 0.114 * color.b as f64
+    }
+
+    #[test]
+    fn larger_asteroids_are_brighter() {
+        for name in Theme::PRESETS {
+            let theme = Theme::preset(name).unwrap();
+            for size in 1..MAX_ASTEROID_SIZE {
+                assert!(
+                    luma(theme.asteroid_color(size)) < luma(theme.asteroid_color(size + 1)),
+                    "{name}, size {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flat_asteroids_are_all_one_color() {
+        let theme = Theme {
+            shade_asteroids_by_size: false,
+            ..Theme::classic()
+        };
+        for size in 1..=MAX_ASTEROID_SIZE {
+            assert_eq!(theme.asteroid_color(size), theme.asteroid);
+        }
+    }
+
+    #[test]
+    fn colorblind_palettes_separate_ships_from_asteroids_by_brightness() {
+        for theme in [Theme::deuteranopia(), Theme::protanopia()] {
+            for size in 1..=MAX_ASTEROID_SIZE {
+                let contrast = luma(theme.ship) - luma(theme.asteroid_color(size));
+                assert!(contrast.abs() >= 100.0, "{}, size {size}", theme.name);
+            }
+        }
+    }
+}
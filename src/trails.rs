@@ -0,0 +1,123 @@
+use sdl2::pixels::Color;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use crate::precision::Float;
+
+/// Converts the fraction of the previous frame that should remain visible
+/// to the opacity of the background drawn over it.
+pub fn fade_alpha(trail_fade: Float) -> u8 {
+    ((1.0 - trail_fade.clamp(0.0, 1.0)) * 255.0).round() as u8
+}
+
+/// Draws each frame over a faded copy of the previous one, so moving things leave trails.
+///
+/// Frames are drawn into a texture, since the window's previous contents aren't kept after
+/// presenting.
+pub struct Trails<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    /// `None` until the first frame, and recreated whenever the window is resized
+    texture: Option<Texture<'a>>,
+    alpha: u8,
+}
+
+impl<'a> Trails<'a> {
+    pub fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        trail_fade: Float,
+    ) -> Trails<'a> {
+        Trails {
+            texture_creator,
+            texture: None,
+            alpha: fade_alpha(trail_fade),
+        }
+    }
+
+    /// Redirects drawing into the trail texture, which is `size` pixels, and fades what was drawn
+    /// there last frame.
+    pub fn begin(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        background: Color,
+        (width, height): (u32, u32),
+    ) -> Result<(), String> {
+        let texture = match &mut self.texture {
+            Some(texture) if (texture.query().width, texture.query().height) == (width, height) => {
+                texture
+            }
+            texture => {
+                let new_texture = self
+                    .texture_creator
+                    .create_texture_target(None, width, height)
+                    .map_err(|e| e.to_string())?;
+                // Start from a cleared texture, rather than whatever was in memory
+                set_render_target(canvas, Some(&new_texture))?;
+                canvas.set_draw_color(background);
+                canvas.clear();
+                texture.insert(new_texture)
+            }
+        };
+        set_render_target(canvas, Some(texture))?;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(
+            background.r,
+            background.g,
+            background.b,
+            self.alpha,
+        ));
+        canvas.fill_rect(None)
+    }
+
+    /// Draws the trail texture to `target`, or to the window if `None`, which is drawn to
+    /// directly again afterwards.
+    pub fn end(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        target: Option<&Texture>,
+    ) -> Result<(), String> {
+        set_render_target(canvas, target)?;
+        if let Some(texture) = &self.texture {
+            canvas.copy(texture, None, None)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws to `texture`, or to the window if `None`.
+///
+/// `sdl2` only exposes render targets through a closure, which the main loop's drawing doesn't
+/// fit into.
+pub fn set_render_target(
+    canvas: &mut WindowCanvas,
+    texture: Option<&Texture>,
+) -> Result<(), String> {
+    let texture = texture.map_or(std::ptr::null_mut(), |texture| texture.raw());
+    // SAFETY: the renderer is live for as long as the canvas, and the texture, if any, was
+    // created by this renderer's texture creator and is live for the duration of the call.
+    let result = unsafe { sdl2::sys::SDL_SetRenderTarget(canvas.raw(), texture) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(sdl2::get_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_alpha_covers_more_the_less_stays() {
+        // Nothing stays, so the background covers the last frame entirely
+        assert_eq!(fade_alpha(0.0), 255);
+        assert_eq!(fade_alpha(0.5), 128);
+        assert_eq!(fade_alpha(0.75), 64);
+        assert_eq!(fade_alpha(1.0), 0);
+    }
+
+    #[test]
+    fn fade_alpha_clamps() {
+        assert_eq!(fade_alpha(-1.0), 255);
+        assert_eq!(fade_alpha(2.0), 0);
+    }
+}